@@ -0,0 +1,246 @@
+use crate::easing::{fraction, Easing, ONE};
+
+/// What an animation does once it reaches the end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    /// stop at the end
+    Once,
+    /// jump back to the start and go again
+    Loop,
+    /// run backwards to the start and then forwards again
+    PingPong,
+}
+
+/// Something that changes over a fixed amount of real time rather than per call, so it moves
+/// at the same speed however busy the microcontroller is. Timestamps are milliseconds from
+/// any monotonic clock and are allowed to wrap around
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Animation {
+    start_ms: u32,
+    duration_ms: u32,
+    easing: Easing,
+    repeat: Repeat,
+}
+
+impl Animation {
+    pub fn new(now_ms: u32, duration_ms: u32, easing: Easing, repeat: Repeat) -> Self {
+        Animation {
+            start_ms: now_ms,
+            duration_ms,
+            easing,
+            repeat,
+        }
+    }
+
+    /// Starts the animation again from the beginning
+    pub fn restart(&mut self, now_ms: u32) {
+        self.start_ms = now_ms;
+    }
+
+    pub fn duration_ms(&self) -> u32 {
+        self.duration_ms
+    }
+
+    /// True once an animation that does not repeat has reached the end
+    pub fn is_finished(&self, now_ms: u32) -> bool {
+        self.repeat == Repeat::Once && self.elapsed(now_ms) >= self.duration_ms
+    }
+
+    /// Eased progress from 0 to ONE
+    pub fn progress(&self, now_ms: u32) -> i32 {
+        if self.duration_ms == 0 {
+            return self.easing.apply(ONE);
+        }
+        let elapsed = self.elapsed(now_ms);
+        let t = match self.repeat {
+            Repeat::Once => fraction(elapsed, self.duration_ms),
+            Repeat::Loop => fraction(elapsed % self.duration_ms, self.duration_ms),
+            Repeat::PingPong => {
+                // a there and back trip can be longer than a u32
+                let duration = self.duration_ms as u64;
+                let elapsed = elapsed as u64 % (2 * duration);
+                let forwards = if elapsed > duration {
+                    2 * duration - elapsed
+                } else {
+                    elapsed
+                };
+                fraction(forwards as u32, self.duration_ms)
+            }
+        };
+        self.easing.apply(t)
+    }
+
+    /// Eased position between two values, for example the x position of some scrolling text
+    pub fn interpolate(&self, now_ms: u32, from: i32, to: i32) -> i32 {
+        let distance = to as i64 - from as i64;
+        (from as i64 + distance * self.progress(now_ms) as i64 / ONE as i64) as i32
+    }
+
+    /// Eased step between 0 and steps, for example to pick the frame of a Transition or an Effect
+    pub fn step(&self, now_ms: u32, steps: u32) -> u32 {
+        self.interpolate(now_ms, 0, steps as i32).max(0) as u32
+    }
+
+    fn elapsed(&self, now_ms: u32) -> u32 {
+        now_ms.wrapping_sub(self.start_ms)
+    }
+}
+
+/// Identifies an animation started by a Scheduler. Ids of animations that have finished stay
+/// stale even once their slot is reused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationId {
+    index: usize,
+    generation: u32,
+}
+
+/// Keeps track of up to N animations at once without using the heap.
+/// Finished animations free up their slot when update is called
+pub struct Scheduler<const N: usize> {
+    slots: [Option<(u32, Animation)>; N],
+    generation: u32,
+}
+
+impl<const N: usize> Scheduler<N> {
+    pub const fn new() -> Self {
+        Scheduler {
+            slots: [None; N],
+            generation: 0,
+        }
+    }
+
+    /// Starts an animation, None if all the slots are in use
+    pub fn start(
+        &mut self,
+        now_ms: u32,
+        duration_ms: u32,
+        easing: Easing,
+        repeat: Repeat,
+    ) -> Option<AnimationId> {
+        let index = self.slots.iter().position(|slot| slot.is_none())?;
+        self.generation = self.generation.wrapping_add(1);
+        let animation = Animation::new(now_ms, duration_ms, easing, repeat);
+        self.slots[index] = Some((self.generation, animation));
+        Some(AnimationId {
+            index,
+            generation: self.generation,
+        })
+    }
+
+    /// Stops an animation and frees its slot
+    pub fn cancel(&mut self, id: AnimationId) {
+        if self.get(id).is_some() {
+            self.slots[id.index] = None;
+        }
+    }
+
+    /// Gets an animation if it is still running
+    pub fn get(&self, id: AnimationId) -> Option<&Animation> {
+        match self.slots.get(id.index)? {
+            Some((generation, animation)) if *generation == id.generation => Some(animation),
+            _ => None,
+        }
+    }
+
+    /// Eased progress of an animation, None once it has finished and been removed
+    pub fn progress(&self, id: AnimationId, now_ms: u32) -> Option<i32> {
+        self.get(id).map(|animation| animation.progress(now_ms))
+    }
+
+    /// Removes finished animations and returns how many are still running
+    pub fn update(&mut self, now_ms: u32) -> usize {
+        for slot in self.slots.iter_mut() {
+            if slot.map_or(false, |(_, animation)| animation.is_finished(now_ms)) {
+                *slot = None;
+            }
+        }
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+}
+
+impl<const N: usize> Default for Scheduler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_wrap_progress() {
+        let looped = Animation::new(100, 1000, Easing::Linear, Repeat::Loop);
+        assert_eq!(looped.progress(100), 0);
+        assert_eq!(looped.progress(600), ONE / 2);
+        assert_eq!(looped.progress(1350), ONE / 4);
+        assert!(!looped.is_finished(1_000_000));
+
+        let ping_pong = Animation::new(100, 1000, Easing::Linear, Repeat::PingPong);
+        assert_eq!(ping_pong.progress(1100), ONE);
+        assert_eq!(ping_pong.progress(1350), ONE * 3 / 4);
+        assert_eq!(ping_pong.progress(2100), 0);
+        assert_eq!(ping_pong.progress(2350), ONE / 4);
+
+        let once = Animation::new(100, 1000, Easing::Linear, Repeat::Once);
+        assert_eq!(once.progress(5000), ONE);
+        assert!(once.is_finished(1100));
+        assert!(!once.is_finished(1099));
+    }
+
+    #[test]
+    fn long_ping_pong_does_not_overflow() {
+        let duration = u32::MAX / 2 + 10;
+        let ping_pong = Animation::new(0, duration, Easing::Linear, Repeat::PingPong);
+        assert_eq!(ping_pong.progress(duration), ONE);
+        assert!(ping_pong.progress(u32::MAX) < ONE);
+    }
+
+    #[test]
+    fn timestamps_may_wrap_around() {
+        let start = u32::MAX - 100;
+        let animation = Animation::new(start, 400, Easing::Linear, Repeat::Once);
+        assert_eq!(animation.progress(99), ONE / 2);
+        assert_eq!(animation.interpolate(99, 0, 10), 5);
+        assert!(animation.is_finished(299));
+    }
+
+    #[test]
+    fn interpolate_handles_wide_ranges() {
+        let animation = Animation::new(0, 100, Easing::Linear, Repeat::Once);
+        assert_eq!(animation.interpolate(50, i32::MIN, i32::MAX), -1);
+        assert_eq!(animation.interpolate(100, i32::MIN, i32::MAX), i32::MAX);
+    }
+
+    #[test]
+    fn finished_animations_free_their_slot() {
+        let mut scheduler = Scheduler::<2>::new();
+        let short = scheduler
+            .start(0, 100, Easing::Linear, Repeat::Once)
+            .unwrap();
+        let looped = scheduler
+            .start(0, 100, Easing::Linear, Repeat::Loop)
+            .unwrap();
+        assert!(scheduler
+            .start(0, 100, Easing::Linear, Repeat::Once)
+            .is_none());
+
+        assert_eq!(scheduler.update(50), 2);
+        assert_eq!(scheduler.update(100), 1);
+        assert!(scheduler.get(short).is_none());
+        assert_eq!(scheduler.progress(looped, 150), Some(ONE / 2));
+
+        // the freed slot is reused but the old id stays stale
+        let newer = scheduler
+            .start(200, 1000, Easing::Linear, Repeat::Once)
+            .unwrap();
+        assert!(scheduler.progress(short, 300).is_none());
+        assert_eq!(scheduler.progress(newer, 700), Some(ONE / 2));
+
+        // cancelling with a stale id leaves the new animation alone
+        scheduler.cancel(short);
+        assert!(scheduler.get(newer).is_some());
+        scheduler.cancel(newer);
+        assert!(scheduler.get(newer).is_none());
+    }
+}
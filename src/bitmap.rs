@@ -0,0 +1,145 @@
+use crate::canvas::Canvas;
+
+/// How the pixels of a bitmap are combined with what is already on the canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    /// lit pixels of the bitmap are turned on
+    Or,
+    /// pixels stay lit only where the bitmap is lit too
+    And,
+    /// lit pixels of the bitmap toggle the canvas
+    Xor,
+    /// lit pixels of the bitmap turn the canvas off, like a rubber
+    AndNot,
+    /// the canvas takes the value of the bitmap, lit or not
+    Replace,
+}
+
+impl BlendMode {
+    /// Combines a pixel already on the canvas with a pixel from a bitmap
+    pub fn blend(self, canvas: bool, bitmap: bool) -> bool {
+        match self {
+            BlendMode::Or => canvas | bitmap,
+            BlendMode::And => canvas & bitmap,
+            BlendMode::Xor => canvas ^ bitmap,
+            BlendMode::AndNot => canvas & !bitmap,
+            BlendMode::Replace => bitmap,
+        }
+    }
+}
+
+/// A read-only monochrome image of any size, usually stored in flash as a const
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bitmap<'a> {
+    width: i32,
+    height: i32,
+    data: Data<'a>,
+}
+
+/// The two layouts a bitmap can be stored in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Data<'a> {
+    /// top row first, leftmost pixel in the most significant bit
+    Rows(&'a [u8]),
+    /// an 8x8 font glyph, bottom row first, leftmost pixel in the least significant bit
+    Glyph(&'a [u8; 8]),
+}
+
+impl<'a> Bitmap<'a> {
+    /// Wraps some bitmap data with rows top first, the MSB is the leftmost pixel and each row is
+    /// byte aligned. Panics (at compile time for a const) if there is too little data
+    pub const fn new(width: i32, height: i32, data: &'a [u8]) -> Self {
+        assert!(width >= 0 && height >= 0);
+        assert!(data.len() >= Self::bytes_per_row(width) * height as usize);
+        Bitmap {
+            width,
+            height,
+            data: Data::Rows(data),
+        }
+    }
+
+    /// Wraps a single 8x8 glyph from a font, for example to use a CP437 symbol as an icon.
+    /// The glyph keeps the font layout (bottom row first, leftmost pixel in the least
+    /// significant bit) and is drawn the right way up
+    pub const fn from_glyph(glyph: &'a [u8; 8]) -> Self {
+        Bitmap {
+            width: 8,
+            height: 8,
+            data: Data::Glyph(glyph),
+        }
+    }
+
+    const fn bytes_per_row(width: i32) -> usize {
+        (width as usize + 7) / 8
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// True if the pixel is lit, false for pixels outside of the bitmap
+    pub fn pixel(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return false;
+        }
+        match self.data {
+            Data::Rows(data) => {
+                let byte = data[y as usize * Self::bytes_per_row(self.width) + x as usize / 8];
+                byte & (0x80 >> (x % 8)) != 0
+            }
+            Data::Glyph(glyph) => glyph[7 - y as usize] & (1 << x) != 0,
+        }
+    }
+}
+
+/// Draws a bitmap with its top left corner at x, y (which can be negative). Only the part that
+/// overlaps the canvas is drawn. If a mask is given (aligned with the bitmap) only the pixels
+/// where the mask is lit are touched and the rest of the canvas shows through
+pub fn blit<C: Canvas + ?Sized>(
+    canvas: &mut C,
+    bitmap: &Bitmap,
+    x: i32,
+    y: i32,
+    mode: BlendMode,
+    mask: Option<&Bitmap>,
+) {
+    let (left, top) = ((-x).max(0), (-y).max(0));
+    let right = bitmap.width().min(canvas.width() - x);
+    let bottom = bitmap.height().min(canvas.height() - y);
+
+    for by in top..bottom {
+        for bx in left..right {
+            if mask.map_or(false, |mask| !mask.pixel(bx, by)) {
+                continue;
+            }
+            let (cx, cy) = (x + bx, y + by);
+            let on = mode.blend(canvas.pixel(cx, cy), bitmap.pixel(bx, by));
+            canvas.set_pixel(cx, cy, on);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_layouts_put_the_same_pixel_top_left() {
+        // a single lit pixel at x 1, y 0 and another at x 9, y 1
+        let rows = Bitmap::new(10, 2, &[0x40, 0x00, 0x00, 0x40]);
+        assert!(rows.pixel(1, 0));
+        assert!(rows.pixel(9, 1));
+        assert!(!rows.pixel(0, 0));
+
+        // glyphs are stored bottom row first with the leftmost pixel in bit 0
+        let glyph = Bitmap::from_glyph(&[0, 0, 0, 0, 0, 0, 0x80, 0x02]);
+        assert!(glyph.pixel(1, 0));
+        assert!(glyph.pixel(7, 1));
+        assert!(!glyph.pixel(6, 0));
+        assert!(!glyph.pixel(8, 0));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{Command, Error, Target, MAX7219};
+use embedded_hal::{blocking::spi::Transfer, digital::v2::OutputPin};
+
+/// Blinks whole devices by toggling the shutdown (OnOff) register, the chips keep their
+/// display data while shut down so nothing needs to be redrawn
+pub struct Blink {
+    target: Target,
+    on_ms: u32,
+    off_ms: u32,
+    start_ms: u32,
+    sent: Option<bool>,
+}
+
+impl Blink {
+    /// Starts blinking with the display on for on_ms and then off for off_ms
+    pub fn new(target: Target, on_ms: u32, off_ms: u32, now_ms: u32) -> Self {
+        Blink {
+            target,
+            on_ms,
+            off_ms,
+            start_ms: now_ms,
+            sent: None,
+        }
+    }
+
+    /// True if the display should be on at a point in time
+    pub fn is_on(&self, now_ms: u32) -> bool {
+        let period = self.on_ms.saturating_add(self.off_ms);
+        period == 0 || now_ms.wrapping_sub(self.start_ms) % period < self.on_ms
+    }
+
+    /// Switches the target devices on or off if the blink state has changed
+    pub fn update<CS, PinError, SpiError>(
+        &mut self,
+        max7219: &mut MAX7219<CS>,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+        now_ms: u32,
+    ) -> Result<(), Error<SpiError, PinError>>
+    where
+        CS: OutputPin<Error = PinError>,
+    {
+        let on = self.is_on(now_ms);
+        if self.sent != Some(on) {
+            max7219.write_command_to(spi, self.target, Command::OnOff, on as u8)?;
+            self.sent = Some(on);
+        }
+        Ok(())
+    }
+
+    /// Stops blinking and leaves the target devices switched on
+    pub fn stop<CS, PinError, SpiError>(
+        &mut self,
+        max7219: &mut MAX7219<CS>,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+    ) -> Result<(), Error<SpiError, PinError>>
+    where
+        CS: OutputPin<Error = PinError>,
+    {
+        max7219.write_command_to(spi, self.target, Command::OnOff, 1)?;
+        self.sent = Some(true);
+        Ok(())
+    }
+}
+
+/// A time of day as minutes since midnight
+pub fn minutes(hours: u8, minutes: u8) -> u16 {
+    hours as u16 * 60 + minutes as u16
+}
+
+/// Shuts devices down during a window of the day (which may wrap past midnight) and wakes them
+/// up again afterwards. The chips keep their display data and intensity while shut down so the
+/// previous content and brightness come back on waking. If something else may have changed the
+/// intensity in the meantime use set_intensity to have it written again on waking
+pub struct SleepSchedule {
+    target: Target,
+    sleep_at: u16,
+    wake_at: u16,
+    intensity: Option<u8>,
+    asleep: Option<bool>,
+}
+
+impl SleepSchedule {
+    /// sleep_at and wake_at are minutes since midnight, see minutes()
+    pub fn new(target: Target, sleep_at: u16, wake_at: u16) -> Self {
+        SleepSchedule {
+            target,
+            sleep_at,
+            wake_at,
+            intensity: None,
+            asleep: None,
+        }
+    }
+
+    /// An intensity to write when waking up from sleep, None (the default) leaves the
+    /// intensity alone
+    pub fn set_intensity(&mut self, intensity: Option<u8>) {
+        self.intensity = intensity;
+    }
+
+    /// True if the devices should be asleep at a time of day (minutes since midnight)
+    pub fn is_asleep(&self, time_of_day: u16) -> bool {
+        if self.sleep_at <= self.wake_at {
+            time_of_day >= self.sleep_at && time_of_day < self.wake_at
+        } else {
+            time_of_day >= self.sleep_at || time_of_day < self.wake_at
+        }
+    }
+
+    /// Shuts the target devices down or wakes them up if the schedule says so.
+    /// The caller supplies the time of day as minutes since midnight
+    pub fn update<CS, PinError, SpiError>(
+        &mut self,
+        max7219: &mut MAX7219<CS>,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+        time_of_day: u16,
+    ) -> Result<(), Error<SpiError, PinError>>
+    where
+        CS: OutputPin<Error = PinError>,
+    {
+        let asleep = self.is_asleep(time_of_day);
+        if self.asleep == Some(asleep) {
+            return Ok(());
+        }
+
+        if asleep {
+            max7219.write_command_to(spi, self.target, Command::OnOff, 0)?;
+        } else {
+            // only when waking from sleep, the first update should not touch the brightness
+            if let (Some(true), Some(intensity)) = (self.asleep, self.intensity) {
+                max7219.write_command_to(spi, self.target, Command::Intensity, intensity)?;
+            }
+            max7219.write_command_to(spi, self.target, Command::OnOff, 1)?;
+        }
+        self.asleep = Some(asleep);
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use crate::fade::intensity_for_percent;
+use crate::{Command, Error, MAX7219};
+use embedded_hal::{blocking::spi::Transfer, digital::v2::OutputPin};
+
+/// Brightness curve for a 12 bit light sensor where higher readings mean more light.
+/// Each point is a (reading, brightness percent) pair
+pub const DEFAULT_CURVE: [(u16, u8); 4] = [(0, 5), (500, 30), (2000, 70), (4095, 100)];
+
+/// Anything that can measure the ambient light, higher readings mean more light
+pub trait LightSensor {
+    type Error;
+
+    fn read_light(&mut self) -> Result<u16, Self::Error>;
+}
+
+/// Reads a light sensor (for example a photoresistor in a voltage divider) connected to an
+/// adc channel using embedded-hal's OneShot trait
+#[cfg(feature = "adc")]
+pub struct AdcLightSensor<'a, ADC, A, PIN> {
+    adc: &'a mut A,
+    pin: PIN,
+    _adc: core::marker::PhantomData<ADC>,
+}
+
+#[cfg(feature = "adc")]
+impl<'a, ADC, A, PIN> AdcLightSensor<'a, ADC, A, PIN>
+where
+    A: embedded_hal::adc::OneShot<ADC, u16, PIN>,
+    PIN: embedded_hal::adc::Channel<ADC>,
+{
+    pub fn new(adc: &'a mut A, pin: PIN) -> Self {
+        AdcLightSensor {
+            adc,
+            pin,
+            _adc: core::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "adc")]
+impl<'a, ADC, A, PIN> LightSensor for AdcLightSensor<'a, ADC, A, PIN>
+where
+    A: embedded_hal::adc::OneShot<ADC, u16, PIN>,
+    PIN: embedded_hal::adc::Channel<ADC>,
+{
+    type Error = A::Error;
+
+    fn read_light(&mut self) -> Result<u16, A::Error> {
+        nb::block!(self.adc.read(&mut self.pin))
+    }
+}
+
+/// Sets the Intensity register of the whole chain from ambient light readings.
+/// Readings are smoothed with a moving average and the brightness only changes once the
+/// average has moved further than the hysteresis from the reading the current brightness was
+/// chosen at. Changes are made one intensity level at a time, no faster than the update
+/// interval, so the display never visibly flickers
+pub struct AutoBrightness<'a> {
+    curve: &'a [(u16, u8)],
+    // readings scaled up by 2^smoothing so the average keeps some precision
+    average: Option<u32>,
+    smoothing: u8,
+    hysteresis: u16,
+    interval_ms: u32,
+    last_update_ms: Option<u32>,
+    reference: Option<u16>,
+    intensity: Option<u8>,
+}
+
+impl<'a> AutoBrightness<'a> {
+    /// curve is a list of (reading, brightness percent) points sorted by reading, brightness
+    /// between the points is interpolated
+    pub fn new(curve: &'a [(u16, u8)]) -> Self {
+        AutoBrightness {
+            curve,
+            average: None,
+            smoothing: 3,
+            hysteresis: 50,
+            interval_ms: 250,
+            last_update_ms: None,
+            reference: None,
+            intensity: None,
+        }
+    }
+
+    /// Each new reading counts for 1 / 2^smoothing of the average (3 by default)
+    pub fn set_smoothing(&mut self, smoothing: u8) {
+        self.smoothing = smoothing.min(15);
+        self.average = None;
+    }
+
+    /// How far the average reading must move before the brightness changes (50 by default)
+    pub fn set_hysteresis(&mut self, hysteresis: u16) {
+        self.hysteresis = hysteresis;
+    }
+
+    /// Minimum time between intensity changes (250ms by default)
+    pub fn set_interval(&mut self, interval_ms: u32) {
+        self.interval_ms = interval_ms;
+    }
+
+    /// Adds a reading to the moving average
+    pub fn add_reading(&mut self, reading: u16) {
+        let scaled = (reading as u32) << self.smoothing;
+        self.average = Some(match self.average {
+            Some(average) => average - (average >> self.smoothing) + reading as u32,
+            None => scaled,
+        });
+    }
+
+    /// Takes a reading from a sensor and adds it to the moving average
+    pub fn read_sensor<S: LightSensor>(&mut self, sensor: &mut S) -> Result<(), S::Error> {
+        let reading = sensor.read_light()?;
+        self.add_reading(reading);
+        Ok(())
+    }
+
+    /// The smoothed reading, None before the first reading
+    pub fn average(&self) -> Option<u16> {
+        self.average
+            .map(|average| (average >> self.smoothing) as u16)
+    }
+
+    /// The intensity last sent to the chips
+    pub fn intensity(&self) -> Option<u8> {
+        self.intensity
+    }
+
+    /// Moves the intensity of every chip one level towards the one the curve gives for the
+    /// average reading, if the update interval has passed
+    pub fn update<CS, PinError, SpiError>(
+        &mut self,
+        max7219: &mut MAX7219<CS>,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+        now_ms: u32,
+    ) -> Result<(), Error<SpiError, PinError>>
+    where
+        CS: OutputPin<Error = PinError>,
+    {
+        let average = match self.average() {
+            Some(average) => average,
+            None => return Ok(()),
+        };
+        if let Some(last) = self.last_update_ms {
+            if now_ms.wrapping_sub(last) < self.interval_ms {
+                return Ok(());
+            }
+        }
+
+        let reference = match self.reference {
+            Some(reference) if average.abs_diff(reference) <= self.hysteresis => reference,
+            _ => average,
+        };
+        self.reference = Some(reference);
+
+        let wanted = intensity_for_percent(self.percent_for(reference)).unwrap_or(0);
+        let next = match self.intensity {
+            Some(current) if current < wanted => current + 1,
+            Some(current) if current > wanted => current - 1,
+            Some(_) => return Ok(()),
+            None => wanted,
+        };
+
+        max7219.write_command_all(spi, Command::Intensity, next)?;
+        self.intensity = Some(next);
+        self.last_update_ms = Some(now_ms);
+        Ok(())
+    }
+
+    /// Brightness percent for a reading by interpolating between the points of the curve
+    fn percent_for(&self, reading: u16) -> u8 {
+        let mut previous: Option<(u16, u8)> = None;
+        for &(x, percent) in self.curve {
+            if reading <= x {
+                return match previous {
+                    Some((x0, p0)) if x > x0 => {
+                        let (x0, p0, x, p) = (x0 as i32, p0 as i32, x as i32, percent as i32);
+                        (p0 + (p - p0) * (reading as i32 - x0) / (x - x0)) as u8
+                    }
+                    _ => percent,
+                };
+            }
+            previous = Some((x, percent));
+        }
+        previous.map_or(100, |(_, percent)| percent)
+    }
+}
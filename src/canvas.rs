@@ -0,0 +1,131 @@
+/// A monochrome surface that can be drawn on. The origin is the top left corner, x grows to the
+/// right and y grows downwards. Pixels outside of the surface are ignored when written to and
+/// read back as off so callers can draw at any position, including negative ones
+pub trait Canvas {
+    /// Width in pixels
+    fn width(&self) -> i32;
+
+    /// Height in pixels
+    fn height(&self) -> i32;
+
+    /// Returns true if the led at x, y is on
+    fn pixel(&self, x: i32, y: i32) -> bool;
+
+    /// Turns the led at x, y on or off
+    fn set_pixel(&mut self, x: i32, y: i32, on: bool);
+
+    /// Turns every led off
+    fn clear(&mut self) {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                self.set_pixel(x, y, false);
+            }
+        }
+    }
+}
+
+/// A rectangular part of another canvas. Drawing is offset by the position of the window and
+/// anything outside of it is ignored so content cannot bleed into the rest of the canvas
+pub struct Window<'a, C: ?Sized> {
+    canvas: &'a mut C,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl<'a, C: Canvas + ?Sized> Window<'a, C> {
+    pub fn new(canvas: &'a mut C, x: i32, y: i32, width: i32, height: i32) -> Self {
+        Window {
+            canvas,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width && y < self.height
+    }
+}
+
+impl<'a, C: Canvas + ?Sized> Canvas for Window<'a, C> {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> bool {
+        self.contains(x, y) && self.canvas.pixel(self.x + x, self.y + y)
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, on: bool) {
+        if self.contains(x, y) {
+            self.canvas.set_pixel(self.x + x, self.y + y, on);
+        }
+    }
+}
+
+/// A position on a canvas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const fn new(x: i32, y: i32) -> Self {
+        Point { x, y }
+    }
+}
+
+/// An area of a canvas given by its top left corner and size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The x position just past the right hand edge
+    pub fn right(&self) -> i32 {
+        self.x + self.width
+    }
+
+    /// The y position just below the bottom edge
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.right() && y < self.bottom()
+    }
+
+    /// The area covered by both rectangles, which is empty if they don't overlap
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Rect::new(x, y, (right - x).max(0), (bottom - y).max(0))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+}
@@ -0,0 +1,207 @@
+use crate::bitmap::Bitmap;
+use crate::canvas::{Canvas, Point, Rect};
+
+/// Most clip rectangles and masks that can be pushed at once
+pub const MAX_CLIP_DEPTH: usize = 8;
+
+/// Returned when pushing onto a clip stack that already holds MAX_CLIP_DEPTH entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipStackFull;
+
+#[derive(Clone, Copy)]
+struct Clip<'m> {
+    // already intersected with every rectangle below it on the stack
+    rect: Rect,
+    mask: Option<(Bitmap<'m>, Point)>,
+}
+
+/// Wraps a canvas with a stack of clip rectangles and masks. Everything drawn through it, be it
+/// text, bitmaps, sprites or shapes, only lands inside the current clip rectangle and where
+/// every pushed mask is lit. Reading pixels is not clipped
+pub struct Clipped<'a, 'm, C: ?Sized> {
+    canvas: &'a mut C,
+    stack: [Option<Clip<'m>>; MAX_CLIP_DEPTH],
+    depth: usize,
+}
+
+impl<'a, 'm, C: Canvas + ?Sized> Clipped<'a, 'm, C> {
+    pub fn new(canvas: &'a mut C) -> Self {
+        Clipped {
+            canvas,
+            stack: [None; MAX_CLIP_DEPTH],
+            depth: 0,
+        }
+    }
+
+    /// The area drawing is currently limited to
+    pub fn clip_rect(&self) -> Rect {
+        match self.top() {
+            Some(clip) => clip.rect,
+            None => Rect::new(0, 0, self.canvas.width(), self.canvas.height()),
+        }
+    }
+
+    /// Limits drawing to the part of rect inside the current clip rectangle
+    pub fn push_rect(&mut self, rect: Rect) -> Result<(), ClipStackFull> {
+        self.push(rect, None)
+    }
+
+    /// Limits drawing to the lit pixels of a mask with its top left corner at x, y
+    pub fn push_mask(&mut self, mask: Bitmap<'m>, x: i32, y: i32) -> Result<(), ClipStackFull> {
+        let rect = Rect::new(x, y, mask.width(), mask.height());
+        self.push(rect, Some((mask, Point::new(x, y))))
+    }
+
+    /// Removes the most recently pushed rectangle or mask
+    pub fn pop(&mut self) {
+        if self.depth > 0 {
+            self.depth -= 1;
+            self.stack[self.depth] = None;
+        }
+    }
+
+    fn push(&mut self, rect: Rect, mask: Option<(Bitmap<'m>, Point)>) -> Result<(), ClipStackFull> {
+        if self.depth == MAX_CLIP_DEPTH {
+            return Err(ClipStackFull);
+        }
+        let rect = self.clip_rect().intersect(&rect);
+        self.stack[self.depth] = Some(Clip { rect, mask });
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn top(&self) -> Option<&Clip<'m>> {
+        self.depth
+            .checked_sub(1)
+            .and_then(|index| self.stack[index].as_ref())
+    }
+
+    fn is_visible(&self, x: i32, y: i32) -> bool {
+        self.clip_rect().contains(x, y)
+            && self.stack[..self.depth].iter().flatten().all(|clip| {
+                clip.mask.map_or(true, |(mask, origin)| {
+                    mask.pixel(x - origin.x, y - origin.y)
+                })
+            })
+    }
+}
+
+impl<'a, 'm, C: Canvas + ?Sized> Canvas for Clipped<'a, 'm, C> {
+    fn width(&self) -> i32 {
+        self.canvas.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.canvas.height()
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> bool {
+        self.canvas.pixel(x, y)
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, on: bool) {
+        if self.is_visible(x, y) {
+            self.canvas.set_pixel(x, y, on);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitmap::{blit, BlendMode};
+    use crate::draw::{fill_rect, line, Pen};
+    use crate::text::{draw_str, Direction};
+    use crate::{FrameBuffer, CP437FONT};
+
+    fn checkerboard() -> FrameBuffer<4> {
+        let mut fb = FrameBuffer::<4>::new();
+        for y in 0..8 {
+            for x in 0..32 {
+                fb.set_pixel(x, y, (x + y) & 1 == 0);
+            }
+        }
+        fb
+    }
+
+    #[test]
+    fn nested_rects_intersect_and_pop_restores() {
+        let mut fb = FrameBuffer::<4>::new();
+        let mut clipped = Clipped::new(&mut fb);
+        assert_eq!(clipped.clip_rect(), Rect::new(0, 0, 32, 8));
+        clipped.push_rect(Rect::new(0, 0, 16, 8)).unwrap();
+        clipped.push_rect(Rect::new(8, 2, 16, 4)).unwrap();
+        assert_eq!(clipped.clip_rect(), Rect::new(8, 2, 8, 4));
+        clipped.pop();
+        assert_eq!(clipped.clip_rect(), Rect::new(0, 0, 16, 8));
+        clipped.pop();
+        clipped.pop();
+        assert_eq!(clipped.clip_rect(), Rect::new(0, 0, 32, 8));
+    }
+
+    #[test]
+    fn stack_is_full_at_max_depth() {
+        let mut fb = FrameBuffer::<4>::new();
+        let mut clipped = Clipped::new(&mut fb);
+        for _ in 0..MAX_CLIP_DEPTH {
+            clipped.push_rect(Rect::new(0, 0, 32, 8)).unwrap();
+        }
+        assert_eq!(clipped.push_rect(Rect::new(0, 0, 8, 8)), Err(ClipStackFull));
+        let mask = Bitmap::new(1, 1, &[0x80]);
+        assert_eq!(clipped.push_mask(mask, 0, 0), Err(ClipStackFull));
+        clipped.pop();
+        assert_eq!(clipped.push_rect(Rect::new(0, 0, 8, 8)), Ok(()));
+    }
+
+    #[test]
+    fn masks_combine_with_rects() {
+        let mut fb = FrameBuffer::<4>::new();
+        let mut clipped = Clipped::new(&mut fb);
+        clipped.push_rect(Rect::new(0, 0, 4, 8)).unwrap();
+        // a mask 8 pixels wide on row 3 with every other pixel lit
+        clipped.push_mask(Bitmap::new(8, 1, &[0xAA]), 0, 3).unwrap();
+        fill_rect(&mut clipped, Rect::new(0, 0, 32, 8), Pen::Set);
+        for y in 0..8 {
+            for x in 0..32 {
+                assert_eq!(fb.pixel(x, y), y == 3 && (x == 0 || x == 2), "{} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn nothing_lands_outside_the_clip() {
+        let clip = Rect::new(10, 2, 9, 4);
+        let before = checkerboard();
+        let mut fb = checkerboard();
+        let mut clipped = Clipped::new(&mut fb);
+        clipped.push_rect(clip).unwrap();
+        draw_str(&mut clipped, &CP437FONT, "MW", 4, 0, Direction::LeftToRight);
+        let block = [0xFF; 8];
+        blit(
+            &mut clipped,
+            &Bitmap::new(8, 8, &block),
+            16,
+            -3,
+            BlendMode::Xor,
+            None,
+        );
+        line(
+            &mut clipped,
+            Point::new(0, 0),
+            Point::new(31, 7),
+            Pen::Toggle,
+        );
+
+        let mut changed_inside = false;
+        for y in 0..8 {
+            for x in 0..32 {
+                if clip.contains(x, y) {
+                    changed_inside |= fb.pixel(x, y) != before.pixel(x, y);
+                } else {
+                    assert_eq!(fb.pixel(x, y), before.pixel(x, y), "{} {}", x, y);
+                }
+            }
+        }
+        assert!(changed_inside);
+    }
+}
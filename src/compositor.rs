@@ -0,0 +1,108 @@
+use crate::bitmap::BlendMode;
+use crate::canvas::Canvas;
+use crate::framebuffer::FrameBuffer;
+use crate::{Error, MAX7219};
+use embedded_hal::{blocking::spi::Transfer, digital::v2::OutputPin};
+
+/// A framebuffer that is drawn on top of the layers below it
+#[derive(Clone)]
+pub struct Layer<const N: usize> {
+    pub framebuffer: FrameBuffer<N>,
+    /// hidden layers are skipped when composing
+    pub visible: bool,
+    /// where the top left corner of the layer ends up on the display
+    pub x: i32,
+    pub y: i32,
+    /// how the layer is combined with the layers below it
+    pub mode: BlendMode,
+}
+
+impl<const N: usize> Layer<N> {
+    /// A visible, empty layer at 0, 0 which ORs onto the layers below
+    pub const fn new() -> Self {
+        Layer {
+            framebuffer: FrameBuffer::new(),
+            visible: true,
+            x: 0,
+            y: 0,
+            mode: BlendMode::Or,
+        }
+    }
+}
+
+impl<const N: usize> Default for Layer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stacks L layers for a chain of N devices so that, for example, a scrolling ticker and a
+/// notification badge can be drawn and updated independently. Layer 0 is at the bottom
+pub struct Compositor<const N: usize, const L: usize> {
+    layers: [Layer<N>; L],
+}
+
+impl<const N: usize, const L: usize> Compositor<N, L> {
+    pub fn new() -> Self {
+        Compositor {
+            layers: core::array::from_fn(|_| Layer::new()),
+        }
+    }
+
+    pub fn layer(&self, index: usize) -> &Layer<N> {
+        &self.layers[index]
+    }
+
+    pub fn layer_mut(&mut self, index: usize) -> &mut Layer<N> {
+        &mut self.layers[index]
+    }
+
+    /// Combines the visible layers from the bottom up into out
+    pub fn compose<C: Canvas + ?Sized>(&self, out: &mut C) {
+        out.clear();
+        for layer in self.layers.iter().filter(|layer| layer.visible) {
+            draw_canvas(out, &layer.framebuffer, layer.x, layer.y, layer.mode);
+        }
+    }
+
+    /// Composes the layers and sends the result to the chips
+    pub fn flush<CS, PinError, SpiError>(
+        &self,
+        max7219: &mut MAX7219<CS>,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+    ) -> Result<(), Error<SpiError, PinError>>
+    where
+        CS: OutputPin<Error = PinError>,
+    {
+        // copy the bottom layer so the output has the same tile layout, compose clears it
+        let mut framebuffer = self
+            .layers
+            .first()
+            .map_or_else(FrameBuffer::new, |layer| layer.framebuffer.clone());
+        self.compose(&mut framebuffer);
+        max7219.write_framebuffer(spi, &framebuffer)
+    }
+}
+
+impl<const N: usize, const L: usize> Default for Compositor<N, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Draws one canvas onto another with its top left corner at x, y
+pub fn draw_canvas<C: Canvas + ?Sized, S: Canvas + ?Sized>(
+    canvas: &mut C,
+    source: &S,
+    x: i32,
+    y: i32,
+    mode: BlendMode,
+) {
+    for sy in 0..source.height() {
+        for sx in 0..source.width() {
+            let (cx, cy) = (x + sx, y + sy);
+            let on = mode.blend(canvas.pixel(cx, cy), source.pixel(sx, sy));
+            canvas.set_pixel(cx, cy, on);
+        }
+    }
+}
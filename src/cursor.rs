@@ -0,0 +1,74 @@
+use crate::canvas::Canvas;
+use crate::font::Font;
+use crate::text::{draw_glyph, CHAR_WIDTH};
+use core::fmt;
+
+/// Height of a line of text in pixels
+const LINE_HEIGHT: i32 = 8;
+
+/// Lets you format text straight onto a canvas with write! and writeln!, no heap required.
+/// The cursor keeps a pen position which moves one character cell to the right for every
+/// character drawn. A newline moves the pen to the start of the next row of tiles down
+pub struct TextCursor<'a, C: ?Sized, F: ?Sized> {
+    canvas: &'a mut C,
+    font: &'a F,
+    x: i32,
+    y: i32,
+}
+
+impl<'a, C: Canvas + ?Sized, F: Font + ?Sized> TextCursor<'a, C, F> {
+    /// Creates a cursor with the pen in the top left corner
+    pub fn new(canvas: &'a mut C, font: &'a F) -> Self {
+        TextCursor {
+            canvas,
+            font,
+            x: 0,
+            y: 0,
+        }
+    }
+
+    /// Moves the pen, x and y are the top left corner of the next character
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// Gets the pen position
+    pub fn position(&self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+
+    /// Turns off every led from the pen to the right hand edge of the canvas on the current line.
+    /// Useful after writing a value that may be shorter than the one it replaces
+    pub fn clear_to_end_of_line(&mut self) {
+        for y in self.y..self.y + LINE_HEIGHT {
+            for x in self.x.max(0)..self.canvas.width() {
+                self.canvas.set_pixel(x, y, false);
+            }
+        }
+    }
+
+    /// Mutable access to the canvas being written to, for example to flush it
+    pub fn canvas(&mut self) -> &mut C {
+        self.canvas
+    }
+}
+
+impl<'a, C: Canvas + ?Sized, F: Font + ?Sized> fmt::Write for TextCursor<'a, C, F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.bytes() {
+            match c {
+                b'\n' => {
+                    self.x = 0;
+                    self.y += LINE_HEIGHT;
+                }
+                b'\r' => self.x = 0,
+                _ => {
+                    draw_glyph(self.canvas, &self.font.glyph(c), self.x, self.y);
+                    self.x += CHAR_WIDTH;
+                }
+            }
+        }
+        Ok(())
+    }
+}
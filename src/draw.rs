@@ -0,0 +1,340 @@
+//! Shapes drawn straight onto any Canvas without needing embedded-graphics. Draw through a
+//! Clipped canvas to keep them inside a clip rectangle or mask
+use crate::canvas::{Canvas, Point, Rect};
+
+/// What drawing does to each pixel of a shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pen {
+    /// turns leds on
+    Set,
+    /// turns leds off
+    Clear,
+    /// flips leds, every pixel of a shape is only touched once so shapes can be drawn twice
+    /// to undo them. Polylines that cross themselves are the exception, see polyline
+    Toggle,
+}
+
+impl Pen {
+    /// Applies the pen to a single pixel
+    pub fn plot<C: Canvas + ?Sized>(self, canvas: &mut C, x: i32, y: i32) {
+        let on = match self {
+            Pen::Set => true,
+            Pen::Clear => false,
+            Pen::Toggle => !canvas.pixel(x, y),
+        };
+        canvas.set_pixel(x, y, on);
+    }
+}
+
+/// A straight line from one point to another, both ends included
+pub fn line<C: Canvas + ?Sized>(canvas: &mut C, from: Point, to: Point, pen: Pen) {
+    for p in LinePixels::new(from, to) {
+        pen.plot(canvas, p.x, p.y);
+    }
+}
+
+/// Lines joining each point to the next. Repeat the first point at the end to close the shape.
+/// With a toggle pen pixels where the lines cross or overlap are flipped twice
+pub fn polyline<C: Canvas + ?Sized>(canvas: &mut C, points: &[Point], pen: Pen) {
+    joined(canvas, points, pen);
+    if points.len() == 1 {
+        pen.plot(canvas, points[0].x, points[0].y);
+    }
+}
+
+/// The outline of a triangle. With a toggle pen pixels where a thin triangle's edges overlap
+/// are flipped twice
+pub fn triangle<C: Canvas + ?Sized>(canvas: &mut C, a: Point, b: Point, c: Point, pen: Pen) {
+    joined(canvas, &[a, b, c, a], pen);
+}
+
+/// Lines joining each point to the next. Each line after the first skips the corner it shares
+/// with the line before, and a closed shape skips the point it closes on, so a toggle pen does
+/// not flip corners twice. Checking every earlier line would cost a pass over all of them per
+/// pixel so lines that cross or overlap away from a corner do toggle those pixels twice
+fn joined<C: Canvas + ?Sized>(canvas: &mut C, points: &[Point], pen: Pen) {
+    let segments = points.len().saturating_sub(1);
+    let closed = points.len() > 2 && points.first() == points.last();
+    for (i, pair) in points.windows(2).enumerate() {
+        let skip_first = i > 0;
+        let skip_last = closed && i + 1 == segments;
+        for p in LinePixels::new(pair[0], pair[1]) {
+            let shared = (skip_first && p == pair[0]) || (skip_last && p == pair[1]);
+            if !shared {
+                pen.plot(canvas, p.x, p.y);
+            }
+        }
+    }
+}
+
+/// The pixels of a line from Bresenham's algorithm, both ends included
+struct LinePixels {
+    next: Option<Point>,
+    to: Point,
+    dx: i32,
+    dy: i32,
+    sx: i32,
+    sy: i32,
+    err: i32,
+}
+
+impl LinePixels {
+    fn new(from: Point, to: Point) -> Self {
+        let dx = (to.x - from.x).abs();
+        let dy = -(to.y - from.y).abs();
+        LinePixels {
+            next: Some(from),
+            to,
+            dx,
+            dy,
+            sx: (to.x - from.x).signum(),
+            sy: (to.y - from.y).signum(),
+            err: dx + dy,
+        }
+    }
+}
+
+impl Iterator for LinePixels {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        let current = self.next?;
+        self.next = if current == self.to {
+            None
+        } else {
+            let mut p = current;
+            let e2 = 2 * self.err;
+            if e2 >= self.dy {
+                self.err += self.dy;
+                p.x += self.sx;
+            }
+            if e2 <= self.dx {
+                self.err += self.dx;
+                p.y += self.sy;
+            }
+            Some(p)
+        };
+        Some(current)
+    }
+}
+
+/// The outline of a rectangle
+pub fn rect<C: Canvas + ?Sized>(canvas: &mut C, rect: Rect, pen: Pen) {
+    if rect.is_empty() {
+        return;
+    }
+    let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+    for x in rect.x..=right {
+        pen.plot(canvas, x, rect.y);
+        if bottom != rect.y {
+            pen.plot(canvas, x, bottom);
+        }
+    }
+    for y in rect.y + 1..bottom {
+        pen.plot(canvas, rect.x, y);
+        if right != rect.x {
+            pen.plot(canvas, right, y);
+        }
+    }
+}
+
+/// A solid rectangle
+pub fn fill_rect<C: Canvas + ?Sized>(canvas: &mut C, rect: Rect, pen: Pen) {
+    for y in rect.y..rect.bottom() {
+        for x in rect.x..rect.right() {
+            pen.plot(canvas, x, y);
+        }
+    }
+}
+
+/// The outline of a circle using the midpoint circle algorithm
+pub fn circle<C: Canvas + ?Sized>(canvas: &mut C, center: Point, radius: i32, pen: Pen) {
+    if radius < 0 {
+        return;
+    }
+    let (cx, cy) = (center.x, center.y);
+    let (mut x, mut y) = (0, radius);
+    let mut err = 1 - radius;
+
+    while x <= y {
+        if x == 0 {
+            if y == 0 {
+                pen.plot(canvas, cx, cy);
+            } else {
+                for (px, py) in [(cx, cy + y), (cx, cy - y), (cx + y, cy), (cx - y, cy)] {
+                    pen.plot(canvas, px, py);
+                }
+            }
+        } else if x == y {
+            for (px, py) in [
+                (cx + x, cy + y),
+                (cx - x, cy + y),
+                (cx + x, cy - y),
+                (cx - x, cy - y),
+            ] {
+                pen.plot(canvas, px, py);
+            }
+        } else {
+            for (px, py) in [
+                (cx + x, cy + y),
+                (cx - x, cy + y),
+                (cx + x, cy - y),
+                (cx - x, cy - y),
+                (cx + y, cy + x),
+                (cx - y, cy + x),
+                (cx + y, cy - x),
+                (cx - y, cy - x),
+            ] {
+                pen.plot(canvas, px, py);
+            }
+        }
+
+        x += 1;
+        if err < 0 {
+            err += 2 * x + 1;
+        } else {
+            y -= 1;
+            err += 2 * (x - y) + 1;
+        }
+    }
+}
+
+/// A solid circle drawn one row at a time
+pub fn fill_circle<C: Canvas + ?Sized>(canvas: &mut C, center: Point, radius: i32, pen: Pen) {
+    // radius^2 + radius gives rounder small circles than radius^2
+    let limit = radius * radius + radius;
+    for dy in -radius..=radius {
+        let mut half_width = 0;
+        while (half_width + 1) * (half_width + 1) + dy * dy <= limit {
+            half_width += 1;
+        }
+        for x in center.x - half_width..=center.x + half_width {
+            pen.plot(canvas, x, center.y + dy);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FrameBuffer;
+
+    type Shape = fn(&mut FrameBuffer<4>, Pen);
+
+    const SHAPES: [Shape; 10] = [
+        |fb, pen| line(fb, Point::new(0, 0), Point::new(31, 7), pen),
+        |fb, pen| line(fb, Point::new(5, 7), Point::new(5, 0), pen),
+        |fb, pen| {
+            let points = [
+                Point::new(0, 7),
+                Point::new(8, 0),
+                Point::new(16, 7),
+                Point::new(0, 7),
+            ];
+            polyline(fb, &points, pen)
+        },
+        |fb, pen| {
+            let points = [
+                Point::new(0, 0),
+                Point::new(6, 6),
+                Point::new(6, 0),
+                Point::new(10, 4),
+            ];
+            polyline(fb, &points, pen)
+        },
+        |fb, pen| {
+            triangle(
+                fb,
+                Point::new(2, 1),
+                Point::new(14, 1),
+                Point::new(8, 7),
+                pen,
+            )
+        },
+        |fb, pen| rect(fb, Rect::new(3, 1, 10, 6), pen),
+        |fb, pen| rect(fb, Rect::new(3, 4, 10, 1), pen),
+        |fb, pen| fill_rect(fb, Rect::new(-2, -2, 8, 5), pen),
+        |fb, pen| circle(fb, Point::new(16, 4), 3, pen),
+        |fb, pen| fill_circle(fb, Point::new(16, 4), 5, pen),
+    ];
+
+    fn lit(fb: &FrameBuffer<4>) -> [[bool; 32]; 8] {
+        let mut pixels = [[false; 32]; 8];
+        for (y, row) in pixels.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = fb.pixel(x as i32, y as i32);
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn toggle_touches_every_pixel_once() {
+        for (i, shape) in SHAPES.iter().enumerate() {
+            let mut set = FrameBuffer::<4>::new();
+            shape(&mut set, Pen::Set);
+            let mut toggled = FrameBuffer::<4>::new();
+            shape(&mut toggled, Pen::Toggle);
+            assert_eq!(lit(&toggled), lit(&set), "shape {}", i);
+
+            shape(&mut toggled, Pen::Toggle);
+            assert_eq!(lit(&toggled), [[false; 32]; 8], "shape {}", i);
+        }
+    }
+
+    #[test]
+    fn toggled_crossings_flip_twice() {
+        let points = [
+            Point::new(0, 0),
+            Point::new(6, 6),
+            Point::new(6, 0),
+            Point::new(0, 6),
+        ];
+        let mut fb = FrameBuffer::<4>::new();
+        polyline(&mut fb, &points, Pen::Toggle);
+        assert!(!fb.pixel(3, 3));
+        // the corners are still only flipped once
+        assert!(fb.pixel(6, 6) && fb.pixel(6, 0));
+    }
+
+    #[test]
+    fn circles_of_any_size_toggle_cleanly() {
+        for radius in 0..8 {
+            let mut set = FrameBuffer::<4>::new();
+            circle(&mut set, Point::new(22, 4), radius, Pen::Set);
+            fill_circle(&mut set, Point::new(7, 3), radius, Pen::Set);
+            let mut toggled = FrameBuffer::<4>::new();
+            circle(&mut toggled, Point::new(22, 4), radius, Pen::Toggle);
+            fill_circle(&mut toggled, Point::new(7, 3), radius, Pen::Toggle);
+            assert_eq!(lit(&toggled), lit(&set), "radius {}", radius);
+        }
+    }
+
+    #[test]
+    fn clear_undoes_set() {
+        for shape in SHAPES.iter() {
+            let mut fb = FrameBuffer::<4>::new();
+            shape(&mut fb, Pen::Set);
+            shape(&mut fb, Pen::Clear);
+            assert_eq!(lit(&fb), [[false; 32]; 8]);
+        }
+    }
+
+    #[test]
+    fn lines_include_both_ends() {
+        let mut fb = FrameBuffer::<4>::new();
+        line(&mut fb, Point::new(3, 6), Point::new(10, 2), Pen::Set);
+        assert!(fb.pixel(3, 6) && fb.pixel(10, 2));
+        let count = lit(&fb).iter().flatten().filter(|on| **on).count();
+        assert_eq!(count, 8);
+    }
+
+    #[test]
+    fn circle_of_radius_zero_is_a_dot() {
+        let mut fb = FrameBuffer::<4>::new();
+        circle(&mut fb, Point::new(4, 4), 0, Pen::Toggle);
+        let count = lit(&fb).iter().flatten().filter(|on| **on).count();
+        assert_eq!(count, 1);
+        assert!(fb.pixel(4, 4));
+    }
+}
@@ -0,0 +1,162 @@
+/// Easing curves work in fixed point where ONE is 1.0, so 512 is halfway
+pub const ONE: i32 = 1024;
+
+/// Shapes how an animation moves between its start and end
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Easing {
+    /// constant speed
+    #[default]
+    Linear,
+    /// starts slowly and speeds up
+    EaseIn,
+    /// starts quickly and slows down
+    EaseOut,
+    /// slow at both ends
+    EaseInOut,
+    /// bounces up to the end value a few times like a dropped ball, never passing it
+    Bounce,
+    /// overshoots and wobbles around the end like a spring
+    Elastic,
+}
+
+impl Easing {
+    /// Maps progress t (0 to ONE) onto the curve. Values outside of that range are clamped.
+    /// Elastic briefly goes above ONE
+    pub fn apply(self, t: i32) -> i32 {
+        let t = t.clamp(0, ONE);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t / ONE,
+            Easing::EaseOut => ONE - (ONE - t) * (ONE - t) / ONE,
+            Easing::EaseInOut => {
+                if t < ONE / 2 {
+                    2 * t * t / ONE
+                } else {
+                    ONE - 2 * (ONE - t) * (ONE - t) / ONE
+                }
+            }
+            Easing::Bounce => bounce(t),
+            Easing::Elastic => elastic(t),
+        }
+    }
+}
+
+/// Four parabolas of 7.5625 * t^2 joined together, each bounce lower than the last
+fn bounce(t: i32) -> i32 {
+    let parabola = |offset: i32, base: i32| {
+        let d = t - offset;
+        121 * d * d / (16 * ONE) + base
+    };
+    if t < ONE * 4 / 11 {
+        parabola(0, 0)
+    } else if t < ONE * 8 / 11 {
+        parabola(ONE * 6 / 11, ONE * 3 / 4)
+    } else if t < ONE * 10 / 11 {
+        parabola(ONE * 9 / 11, ONE * 15 / 16)
+    } else {
+        parabola(ONE * 21 / 22, ONE * 63 / 64)
+    }
+}
+
+/// 2^(-10t) * sin((10t - 0.75) * 2pi / 3) + 1
+fn elastic(t: i32) -> i32 {
+    if t == 0 || t == ONE {
+        return t;
+    }
+    let exponent = 10 * t;
+    let whole = exponent / ONE;
+    let part = exponent % ONE;
+    // 2^-part is close enough to 1 - part / 2 for a few leds
+    let decay = (ONE - part / 2) >> whole;
+    let angle = (exponent - ONE * 3 / 4) / 3;
+    decay * sin_turns(angle) / ONE + ONE
+}
+
+/// Sine of an angle measured in turns (ONE is a full turn) using Bhaskara's approximation
+pub fn sin_turns(angle: i32) -> i32 {
+    let angle = angle.rem_euclid(ONE);
+    let (u, sign) = if angle < ONE / 2 {
+        (angle, 1)
+    } else {
+        (angle - ONE / 2, -1)
+    };
+    let p = u * (ONE - 2 * u) / ONE;
+    sign * 32 * p * ONE / (5 * ONE - 8 * p)
+}
+
+/// Progress of step out of steps as a fixed point fraction
+pub fn fraction(step: u32, steps: u32) -> i32 {
+    if steps == 0 {
+        return ONE;
+    }
+    (step.min(steps) as i64 * ONE as i64 / steps as i64) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Easing; 6] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Bounce,
+        Easing::Elastic,
+    ];
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one() {
+        for easing in ALL {
+            assert_eq!(easing.apply(0), 0, "{:?}", easing);
+            assert_eq!(easing.apply(ONE), ONE, "{:?}", easing);
+            assert_eq!(easing.apply(-5), 0, "{:?}", easing);
+            assert_eq!(easing.apply(ONE + 5), ONE, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn smooth_curves_never_go_backwards() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            for t in 1..=ONE {
+                assert!(
+                    easing.apply(t) >= easing.apply(t - 1),
+                    "{:?} at {}",
+                    easing,
+                    t
+                );
+            }
+        }
+        assert!(Easing::EaseIn.apply(ONE / 2) < ONE / 2);
+        assert!(Easing::EaseOut.apply(ONE / 2) > ONE / 2);
+        assert_eq!(Easing::EaseInOut.apply(ONE / 2), ONE / 2);
+    }
+
+    #[test]
+    fn bounce_stays_in_range_and_elastic_overshoots() {
+        assert!((0..=ONE).all(|t| (0..=ONE).contains(&Easing::Bounce.apply(t))));
+        assert!((0..=ONE).any(|t| Easing::Elastic.apply(t) > ONE));
+    }
+
+    #[test]
+    fn sine_hits_the_quarter_turns() {
+        assert_eq!(sin_turns(0), 0);
+        assert_eq!(sin_turns(ONE / 4), ONE);
+        assert_eq!(sin_turns(ONE / 2), 0);
+        assert_eq!(sin_turns(ONE * 3 / 4), -ONE);
+        assert_eq!(sin_turns(-ONE / 4), -ONE);
+    }
+
+    #[test]
+    fn fraction_is_clamped() {
+        assert_eq!(fraction(0, 4), 0);
+        assert_eq!(fraction(1, 4), ONE / 4);
+        assert_eq!(fraction(9, 4), ONE);
+        assert_eq!(fraction(0, 0), ONE);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::canvas::Canvas;
+
+/// Animations used to bring a block of text onto the display or take it away again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// appears or disappears in a single frame
+    Print,
+    /// moves in from the right and out to the left
+    ScrollLeft,
+    /// moves in from the left and out to the right
+    ScrollRight,
+    /// moves in from the bottom and out the top
+    ScrollUp,
+    /// moves in from the top and out the bottom
+    ScrollDown,
+    /// revealed column by column from the left
+    Wipe,
+    /// revealed column by column in every device at the same time like venetian blinds
+    Blinds,
+    /// revealed pixel by pixel in a scattered order
+    Dissolve,
+    /// revealed from the middle outwards
+    OpeningCurtain,
+    /// revealed from both edges inwards
+    ClosingCurtain,
+    /// columns fly in from the right one at a time
+    Slice,
+    /// columns drop into place from above one at a time
+    Drop,
+    /// revealed row by row from the bottom up
+    Grow,
+    /// the pixels stay put while the Intensity register is stepped, see fade_intensity
+    Fade,
+}
+
+/// Whether the text is arriving or leaving
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Entry,
+    Exit,
+}
+
+/// Number of frames an effect takes on a canvas of the given size
+pub fn frame_count(effect: Effect, width: i32, height: i32) -> u32 {
+    let frames = match effect {
+        Effect::Print => 1,
+        Effect::ScrollLeft | Effect::ScrollRight | Effect::Wipe | Effect::Slice => width,
+        Effect::ScrollUp | Effect::ScrollDown | Effect::Grow => height,
+        Effect::Blinds => 8,
+        Effect::Dissolve | Effect::Fade => 16,
+        Effect::OpeningCurtain | Effect::ClosingCurtain => (width + 1) / 2,
+        Effect::Drop => width + height,
+    };
+    frames.max(1) as u32
+}
+
+/// Draws one frame of an effect. content holds the text block as it looks once it is fully
+/// shown and should be the same size as out. Frame 0 is the start of the effect and
+/// frame_count is the last frame (fully shown for an entry, blank for an exit)
+pub fn draw_frame<S: Canvas + ?Sized, C: Canvas + ?Sized>(
+    effect: Effect,
+    phase: Phase,
+    frame: u32,
+    content: &S,
+    out: &mut C,
+) {
+    let (width, height) = (out.width(), out.height());
+    let frames = frame_count(effect, width, height);
+    let frame = frame.min(frames);
+
+    out.clear();
+    for y in 0..height {
+        for x in 0..width {
+            if !content.pixel(x, y) {
+                continue;
+            }
+            if let Some((px, py)) = place(effect, phase, x, y, width, height, frame, frames) {
+                out.set_pixel(px, py, true);
+            }
+        }
+    }
+}
+
+/// Intensity to use for a frame of the Fade effect, between 0 and max_intensity (15 at most)
+pub fn fade_intensity(phase: Phase, frame: u32, max_intensity: u8) -> u8 {
+    let frames = frame_count(Effect::Fade, 0, 0);
+    let shown = progress(phase, frame.min(frames), frames);
+    (max_intensity as u32 * shown / frames) as u8
+}
+
+/// Frames of an entry count up to fully shown, frames of an exit count back down
+fn progress(phase: Phase, frame: u32, frames: u32) -> u32 {
+    match phase {
+        Phase::Entry => frame,
+        Phase::Exit => frames - frame,
+    }
+}
+
+/// Where a lit pixel of the content ends up for a frame, None if it is hidden
+#[allow(clippy::too_many_arguments)]
+fn place(
+    effect: Effect,
+    phase: Phase,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    frame: u32,
+    frames: u32,
+) -> Option<(i32, i32)> {
+    let shown = progress(phase, frame, frames) as i32;
+    let frames = frames as i32;
+    let visible = |is_visible: bool| if is_visible { Some((x, y)) } else { None };
+
+    // scrolling keeps moving the same way when leaving rather than backing out
+    let moved = match phase {
+        Phase::Entry => frames - frame as i32,
+        Phase::Exit => -(frame as i32),
+    };
+
+    match effect {
+        Effect::Print => visible(shown == frames),
+        Effect::ScrollLeft => Some((x + moved, y)),
+        Effect::ScrollRight => Some((x - moved, y)),
+        Effect::ScrollUp => Some((x, y + moved)),
+        Effect::ScrollDown => Some((x, y - moved)),
+        Effect::Wipe => visible(x < shown),
+        Effect::Blinds => visible(x % 8 < shown),
+        Effect::Dissolve => visible((scatter(x, y) as i32 * frames) < shown * 256),
+        Effect::OpeningCurtain => {
+            let from_middle = (2 * x + 1 - width).abs() / 2;
+            visible(from_middle < shown)
+        }
+        Effect::ClosingCurtain => {
+            let from_edge = x.min(width - 1 - x);
+            visible(from_edge < shown)
+        }
+        Effect::Slice => {
+            // column x sets off after frame x and moves two pixels per frame
+            let travelled = shown - x - 1;
+            if travelled < 0 {
+                None
+            } else {
+                Some(((width - 1 - 2 * travelled).max(x), y))
+            }
+        }
+        Effect::Drop => {
+            // column x starts falling at frame x
+            let fallen = shown - x;
+            if fallen < 0 {
+                None
+            } else {
+                Some((x, (y - height + fallen).min(y)))
+            }
+        }
+        Effect::Grow => visible(y >= height - shown),
+        Effect::Fade => Some((x, y)),
+    }
+}
+
+/// A well mixed number between 0 and 255 for each pixel so the dissolve looks random
+pub(crate) fn scatter(x: i32, y: i32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x9E37_79B1) ^ (y as u32).wrapping_mul(0x85EB_CA77);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    h & 0xFF
+}
+
+/// Plays an effect one frame at a time
+pub struct EffectPlayer {
+    effect: Effect,
+    phase: Phase,
+    frame: u32,
+}
+
+impl EffectPlayer {
+    pub fn new(effect: Effect, phase: Phase) -> Self {
+        EffectPlayer {
+            effect,
+            phase,
+            frame: 0,
+        }
+    }
+
+    /// The frame that will be drawn next
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// True once the last frame has been drawn for a canvas of the given size
+    pub fn is_done(&self, width: i32, height: i32) -> bool {
+        self.frame > frame_count(self.effect, width, height)
+    }
+
+    /// Draws the next frame and returns true if there are more frames to come.
+    /// For the Fade effect also send fade_intensity to the chips
+    pub fn step<S: Canvas + ?Sized, C: Canvas + ?Sized>(
+        &mut self,
+        content: &S,
+        out: &mut C,
+    ) -> bool {
+        draw_frame(self.effect, self.phase, self.frame, content, out);
+        self.frame += 1;
+        !self.is_done(out.width(), out.height())
+    }
+
+    /// Intensity for the frame that was last drawn when playing the Fade effect
+    pub fn fade_intensity(&self, max_intensity: u8) -> u8 {
+        fade_intensity(self.phase, self.frame.saturating_sub(1), max_intensity)
+    }
+}
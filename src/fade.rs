@@ -0,0 +1,186 @@
+use crate::animation::{Animation, Repeat};
+use crate::easing::{Easing, ONE};
+use crate::{Command, Error, Target, MAX7219};
+use embedded_hal::{blocking::spi::Transfer, digital::v2::OutputPin};
+
+/// Lowest brightness percentage for each of the 16 intensity levels. The chip lights the leds
+/// for (2 * level + 1) / 32 of the time and the eye sees brightness roughly as duty^(1/2.2)
+/// so the levels are bunched up at the bright end
+const LEVEL_THRESHOLDS: [u8; 16] = [
+    1, 28, 39, 47, 53, 59, 64, 69, 73, 77, 81, 84, 88, 91, 94, 97,
+];
+
+/// Maps a perceived brightness (0 to 100%) onto an Intensity register value (0 to 15).
+/// None means 0% where the display should be shut down because the lowest intensity is still lit
+pub fn intensity_for_percent(percent: u8) -> Option<u8> {
+    LEVEL_THRESHOLDS
+        .iter()
+        .rposition(|threshold| percent >= *threshold)
+        .map(|level| level as u8)
+}
+
+/// The kind of fade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FadeKind {
+    /// from the lowest to the highest brightness, then stays there
+    In,
+    /// from the highest to the lowest brightness, then stays there
+    Out,
+    /// smoothly up and down between the lowest and highest brightness forever
+    Breathe,
+}
+
+/// Steps the Intensity register over time so the whole chain or a single device fades in,
+/// fades out or pulses. Call update as often as you like, the chips are only written to
+/// when the intensity actually changes
+pub struct Fade {
+    target: Target,
+    kind: FadeKind,
+    animation: Animation,
+    min_percent: u8,
+    max_percent: u8,
+    sent: Option<Option<u8>>,
+}
+
+impl Fade {
+    /// duration_ms is the length of the fade, or the time from dim to bright when breathing
+    pub fn new(target: Target, kind: FadeKind, duration_ms: u32, now_ms: u32) -> Self {
+        let (easing, repeat) = match kind {
+            FadeKind::Breathe => (Easing::EaseInOut, Repeat::PingPong),
+            _ => (Easing::Linear, Repeat::Once),
+        };
+        Fade {
+            target,
+            kind,
+            animation: Animation::new(now_ms, duration_ms, easing, repeat),
+            min_percent: 0,
+            max_percent: 100,
+            sent: None,
+        }
+    }
+
+    /// Limits the brightness, 0 and 100% by default
+    pub fn set_range(&mut self, min_percent: u8, max_percent: u8) {
+        self.min_percent = min_percent.min(100);
+        self.max_percent = max_percent.min(100);
+    }
+
+    /// Starts the fade again from the beginning
+    pub fn restart(&mut self, now_ms: u32) {
+        self.animation.restart(now_ms);
+    }
+
+    /// True once a fade in or fade out has completed, a breathing fade never finishes
+    pub fn is_finished(&self, now_ms: u32) -> bool {
+        self.animation.is_finished(now_ms)
+    }
+
+    /// Perceived brightness at a point in time
+    pub fn percent(&self, now_ms: u32) -> u8 {
+        let progress = match self.kind {
+            FadeKind::Out => ONE - self.animation.progress(now_ms),
+            _ => self.animation.progress(now_ms),
+        };
+        let (min, max) = (self.min_percent as i32, self.max_percent as i32);
+        (min + (max - min) * progress / ONE) as u8
+    }
+
+    /// Sends the intensity for the current point in time to the target chips if it changed.
+    /// At 0% the chips are shut down and they are woken up again when the brightness comes back
+    pub fn update<CS, PinError, SpiError>(
+        &mut self,
+        max7219: &mut MAX7219<CS>,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+        now_ms: u32,
+    ) -> Result<(), Error<SpiError, PinError>>
+    where
+        CS: OutputPin<Error = PinError>,
+    {
+        let intensity = intensity_for_percent(self.percent(now_ms));
+        if self.sent == Some(intensity) {
+            return Ok(());
+        }
+
+        match intensity {
+            Some(level) => {
+                max7219.write_command_to(spi, self.target, Command::Intensity, level)?;
+                if !matches!(self.sent, Some(Some(_))) {
+                    max7219.write_command_to(spi, self.target, Command::OnOff, 1)?;
+                }
+            }
+            None => max7219.write_command_to(spi, self.target, Command::OnOff, 0)?,
+        }
+        self.sent = Some(intensity);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Pin, Spi};
+
+    const INTENSITY: u8 = Command::Intensity as u8;
+    const ON_OFF: u8 = Command::OnOff as u8;
+
+    #[test]
+    fn thresholds_rise_to_the_brightest_level() {
+        assert!(LEVEL_THRESHOLDS.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(LEVEL_THRESHOLDS[0], 1);
+        assert!(LEVEL_THRESHOLDS[15] <= 100);
+        // half the perceived brightness is only a quarter of the duty cycle
+        assert_eq!(intensity_for_percent(50), Some(3));
+    }
+
+    #[test]
+    fn percent_maps_onto_levels() {
+        assert_eq!(intensity_for_percent(0), None);
+        assert_eq!(intensity_for_percent(1), Some(0));
+        assert_eq!(intensity_for_percent(27), Some(0));
+        assert_eq!(intensity_for_percent(28), Some(1));
+        assert_eq!(intensity_for_percent(96), Some(14));
+        assert_eq!(intensity_for_percent(97), Some(15));
+        assert_eq!(intensity_for_percent(100), Some(15));
+        assert_eq!(intensity_for_percent(255), Some(15));
+        for percent in 1..100 {
+            assert!(intensity_for_percent(percent) <= intensity_for_percent(percent + 1));
+        }
+    }
+
+    #[test]
+    fn fading_out_shuts_the_display_down_at_0() {
+        let (mut pin, mut spi) = (Pin, Spi::new());
+        let mut max7219 = MAX7219::new(&mut pin, 1);
+        let mut fade = Fade::new(Target::All, FadeKind::Out, 100, 0);
+        fade.update(&mut max7219, &mut spi, 0).unwrap();
+        let sent: [(u8, u8); 2] = [(INTENSITY, 15), (ON_OFF, 1)];
+        assert!(spi.commands().eq(sent.iter().copied()));
+
+        spi.clear();
+        fade.update(&mut max7219, &mut spi, 100).unwrap();
+        assert!(spi.commands().eq([(ON_OFF, 0)].iter().copied()));
+
+        // nothing changed so nothing is sent
+        spi.clear();
+        fade.update(&mut max7219, &mut spi, 150).unwrap();
+        assert_eq!(spi.commands().count(), 0);
+    }
+
+    #[test]
+    fn display_is_only_switched_on_when_it_comes_back() {
+        let (mut pin, mut spi) = (Pin, Spi::new());
+        let mut max7219 = MAX7219::new(&mut pin, 1);
+        let mut fade = Fade::new(Target::All, FadeKind::In, 100, 0);
+        fade.update(&mut max7219, &mut spi, 0).unwrap();
+        assert!(spi.commands().eq([(ON_OFF, 0)].iter().copied()));
+
+        spi.clear();
+        fade.update(&mut max7219, &mut spi, 50).unwrap();
+        let sent: [(u8, u8); 2] = [(INTENSITY, 3), (ON_OFF, 1)];
+        assert!(spi.commands().eq(sent.iter().copied()));
+
+        spi.clear();
+        fade.update(&mut max7219, &mut spi, 80).unwrap();
+        assert!(spi.commands().eq([(INTENSITY, 9)].iter().copied()));
+    }
+}
@@ -262,3 +262,22 @@ pub const CP437FONT: [[u8; 8]; 256] = [
     [0x00, 0x00, 0x3C, 0x3C, 0x3C, 0x3C, 0x00, 0x00], // 0xFE
     [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0xFF
 ];
+
+/// An 8x8 font laid out like `CP437FONT`: each glyph is 8 bytes where index 0 is the bottom row
+/// (the Digit0 register) and bit 0 is the leftmost led in that row
+pub trait Font {
+    /// Gets the bit pattern for a single character
+    fn glyph(&self, c: u8) -> [u8; 8];
+}
+
+impl Font for [[u8; 8]; 256] {
+    fn glyph(&self, c: u8) -> [u8; 8] {
+        self[c as usize]
+    }
+}
+
+impl<F: Font + ?Sized> Font for &F {
+    fn glyph(&self, c: u8) -> [u8; 8] {
+        (**self).glyph(c)
+    }
+}
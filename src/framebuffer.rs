@@ -0,0 +1,110 @@
+use crate::canvas::Canvas;
+
+/// An in-memory copy of the display for a chain of N daisy-chained 8x8 devices.
+/// Device 0 is the one closest to the microcontroller and appears on the left. The devices can
+/// also be tiled into several rows, in which case the chain continues at the left of the next
+/// row down. Draw into it using the Canvas trait and send it to the chips with
+/// MAX7219::write_framebuffer
+#[derive(Clone)]
+pub struct FrameBuffer<const N: usize> {
+    devices: [[u8; 8]; N],
+    columns: usize,
+}
+
+impl<const N: usize> FrameBuffer<N> {
+    /// Creates a framebuffer with all leds off and all the devices in a single row
+    pub const fn new() -> Self {
+        Self::tiled(N)
+    }
+
+    /// Creates a framebuffer with all leds off where the devices are arranged in rows of
+    /// `columns` devices. N must be a multiple of columns
+    pub const fn tiled(columns: usize) -> Self {
+        assert!(columns > 0 && N % columns == 0);
+        FrameBuffer {
+            devices: [[0; 8]; N],
+            columns,
+        }
+    }
+
+    /// Number of devices in the chain
+    pub const fn num_devices(&self) -> usize {
+        N
+    }
+
+    /// Number of devices in each row of tiles
+    pub const fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// The raw rows of a single device, in the same layout as a font glyph
+    /// (index 0 is the Digit0 register and bit 0 is the leftmost led)
+    pub fn device(&self, device_index: usize) -> &[u8; 8] {
+        &self.devices[device_index]
+    }
+
+    /// Mutable access to the raw rows of a single device
+    pub fn device_mut(&mut self, device_index: usize) -> &mut [u8; 8] {
+        &mut self.devices[device_index]
+    }
+
+    /// Builds the payload for MAX7219::write_line_raw for one line.
+    /// The first byte shifted out ends up in the last device so the payload is reversed
+    pub fn line(&self, line_index: u8) -> [u8; N] {
+        let mut payload = [0; N];
+        for (i, device) in self.devices.iter().rev().enumerate() {
+            payload[i] = device[line_index as usize];
+        }
+        payload
+    }
+
+    /// Device index and row within that device for a pixel, None if off the display
+    fn locate(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        if x < 0 || y < 0 || x >= self.width() || y >= self.height() {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        Some(((y / 8) * self.columns + x / 8, 7 - y % 8))
+    }
+}
+
+impl<const N: usize> Default for FrameBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Canvas for FrameBuffer<N> {
+    fn width(&self) -> i32 {
+        self.columns as i32 * 8
+    }
+
+    fn height(&self) -> i32 {
+        (N / self.columns) as i32 * 8
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> bool {
+        match self.locate(x, y) {
+            Some((device, row)) => self.devices[device][row] & (1 << (x % 8)) != 0,
+            None => false,
+        }
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, on: bool) {
+        let (device, row) = match self.locate(x, y) {
+            Some(location) => location,
+            None => return,
+        };
+        let row = &mut self.devices[device][row];
+        let mask = 1 << (x % 8);
+        if on {
+            *row |= mask;
+        } else {
+            *row &= !mask;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.devices = [[0; 8]; N];
+    }
+}
@@ -0,0 +1,126 @@
+use crate::canvas::Canvas;
+use crate::framebuffer::FrameBuffer;
+use crate::{Error, MAX7219};
+use embedded_hal::{blocking::spi::Transfer, digital::v2::OutputPin};
+
+/// Bits sent over the spi bus for one full refresh of a single device (8 lines of 16 bits)
+const BITS_PER_DEVICE_REFRESH: u32 = 8 * 16;
+
+/// A framebuffer with 2 to 4 bits of brightness per pixel for a single row of N devices.
+/// The chips can only turn leds on or off so the shades are produced by bit-angle modulation:
+/// every bit plane is sent in turn and held for a time proportional to its weight. This needs a
+/// fast spi bus, see grayscale_refresh_rate_hz.
+/// Drawing through the Canvas trait uses full brightness
+#[derive(Clone)]
+pub struct GrayFrameBuffer<const N: usize> {
+    // two pixels per byte, low nibble first, rows from the top
+    pixels: [[u8; 32]; N],
+    bits: u8,
+}
+
+impl<const N: usize> GrayFrameBuffer<N> {
+    /// Creates a framebuffer with all leds off. bits should be between 2 and 4
+    pub fn new(bits: u8) -> Self {
+        assert!((2..=4).contains(&bits));
+        GrayFrameBuffer {
+            pixels: [[0; 32]; N],
+            bits,
+        }
+    }
+
+    /// Brightest level a pixel can be set to
+    pub fn max_level(&self) -> u8 {
+        (1 << self.bits) - 1
+    }
+
+    /// Brightness of the led at x, y, 0 for pixels off the display
+    pub fn level(&self, x: i32, y: i32) -> u8 {
+        match locate(x, y, N) {
+            Some((device, index, shift)) => (self.pixels[device][index] >> shift) & 0x0F,
+            None => 0,
+        }
+    }
+
+    /// Sets the brightness of the led at x, y, levels above max_level are clamped
+    pub fn set_level(&mut self, x: i32, y: i32, level: u8) {
+        let level = level.min(self.max_level());
+        if let Some((device, index, shift)) = locate(x, y, N) {
+            let byte = &mut self.pixels[device][index];
+            *byte = (*byte & !(0x0F << shift)) | (level << shift);
+        }
+    }
+
+    /// Fills a framebuffer with a single bit plane, plane 0 being the least significant
+    pub fn plane(&self, plane: u8, out: &mut FrameBuffer<N>) {
+        for y in 0..8 {
+            for x in 0..N as i32 * 8 {
+                out.set_pixel(x, y, self.level(x, y) & (1 << plane) != 0);
+            }
+        }
+    }
+
+    /// Sends one complete grayscale frame to the chips. Each bit plane is sent 2^plane times
+    /// in a row which holds it on the leds in proportion to its weight, so call this in a
+    /// tight loop to keep the image steady
+    pub fn write<CS, PinError, SpiError>(
+        &self,
+        max7219: &mut MAX7219<CS>,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+    ) -> Result<(), Error<SpiError, PinError>>
+    where
+        CS: OutputPin<Error = PinError>,
+    {
+        let mut framebuffer = FrameBuffer::new();
+        for plane in 0..self.bits {
+            self.plane(plane, &mut framebuffer);
+            for _ in 0..1 << plane {
+                max7219.write_framebuffer(spi, &framebuffer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Canvas for GrayFrameBuffer<N> {
+    fn width(&self) -> i32 {
+        N as i32 * 8
+    }
+
+    fn height(&self) -> i32 {
+        8
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> bool {
+        self.level(x, y) != 0
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, on: bool) {
+        let level = if on { self.max_level() } else { 0 };
+        self.set_level(x, y, level);
+    }
+
+    fn clear(&mut self) {
+        self.pixels = [[0; 32]; N];
+    }
+}
+
+/// Device, byte index and bit shift for a pixel, None if off the display
+fn locate(x: i32, y: i32, num_devices: usize) -> Option<(usize, usize, u8)> {
+    if x < 0 || y < 0 || x >= num_devices as i32 * 8 || y >= 8 {
+        return None;
+    }
+    let (x, y) = (x as usize, y as usize);
+    let index = y * 8 + x % 8;
+    Some((x / 8, index / 2, (index % 2) as u8 * 4))
+}
+
+/// The best case number of complete grayscale frames per second for a chain of num_devices
+/// at a given spi clock. Time spent by the microcontroller between transfers is not taken into
+/// account so expect a little less in practice. Below about 100Hz the image will flicker.
+/// bits should be between 2 and 4, the same as for GrayFrameBuffer::new
+pub fn grayscale_refresh_rate_hz(num_devices: usize, spi_hz: u32, bits: u8) -> u32 {
+    assert!((2..=4).contains(&bits));
+    let refreshes_per_frame = (1u32 << bits) - 1;
+    let bits_per_frame = BITS_PER_DEVICE_REFRESH * num_devices as u32 * refreshes_per_frame;
+    spi_hz / bits_per_frame.max(1)
+}
@@ -9,8 +9,51 @@
 extern crate embedded_hal;
 use core::result::Result;
 use embedded_hal::{blocking::spi::Transfer, digital::v2::OutputPin};
+pub mod animation;
+mod bitmap;
+pub mod blink;
+pub mod brightness;
+mod canvas;
+mod clip;
+mod compositor;
+mod cursor;
+pub mod draw;
+pub mod easing;
+pub mod effect;
+pub mod fade;
 mod font;
-use font::*;
+mod framebuffer;
+mod grayscale;
+pub mod markup;
+mod marquee;
+#[cfg(test)]
+mod mock;
+pub mod power;
+mod rotate;
+mod scroll;
+mod sprite;
+mod style;
+pub mod text;
+mod transition;
+mod viewport;
+pub mod widgets;
+mod zone;
+pub use bitmap::{blit, Bitmap, BlendMode};
+pub use canvas::{Canvas, Point, Rect, Window};
+pub use clip::{ClipStackFull, Clipped, MAX_CLIP_DEPTH};
+pub use compositor::{draw_canvas, Compositor, Layer};
+pub use cursor::TextCursor;
+pub use font::{Font, CP437FONT};
+pub use framebuffer::FrameBuffer;
+pub use grayscale::{grayscale_refresh_rate_hz, GrayFrameBuffer};
+pub use marquee::Marquee;
+pub use rotate::{rotate_90_clockwise, rotate_90_counter_clockwise};
+pub use scroll::{scroll, scroll_bitmap, ScrollDirection};
+pub use sprite::{draw_sprites, update_sprites, Edges, Path, Sprite};
+pub use style::{Style, Styled};
+pub use transition::{Frames, Transition, TransitionKind};
+pub use viewport::Viewport;
+pub use zone::{ScrollingText, StaticText, Zone, ZoneContent, Zones};
 
 #[derive(Debug)]
 pub enum Error<SpiError, PinError> {
@@ -43,6 +86,15 @@ pub enum Command {
     DisplayTest = 0x0F,
 }
 
+/// which chips a command is sent to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// every chip in the chain
+    All,
+    /// a single chip where zero is the first chip
+    Device(usize),
+}
+
 pub struct MAX7219<'a, CS> {
     cs: &'a mut CS,
     num_devices: usize,
@@ -73,6 +125,22 @@ where
         Ok(())
     }
 
+    /// Write command to all chips or to a single chip
+    pub fn write_command_to<SpiError>(
+        &mut self,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+        target: Target,
+        command: Command,
+        data: u8,
+    ) -> Result<(), Error<SpiError, PinError>> {
+        match target {
+            Target::All => self.write_raw_all(spi, command as u8, data),
+            Target::Device(device_index) => {
+                self.write_device_raw(spi, device_index, command as u8, data)
+            }
+        }
+    }
+
     /// Clear the display
     pub fn clear_all<SpiError>(
         &mut self,
@@ -133,6 +201,19 @@ where
         Ok(())
     }
 
+    /// Sends the whole framebuffer to the chips, one write_line_raw transaction per line.
+    /// The framebuffer should have num_devices devices in it
+    pub fn write_framebuffer<SpiError, const N: usize>(
+        &mut self,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+        framebuffer: &FrameBuffer<N>,
+    ) -> Result<(), Error<SpiError, PinError>> {
+        for line_index in 0..8 {
+            self.write_line_raw(spi, line_index, &framebuffer.line(line_index))?;
+        }
+        Ok(())
+    }
+
     /// Write a single byte to a chip a certain position where zero is the first chip
     /// this supports daisy chaining multiple chips together.
     /// Note that if you plan to write to all devices then write_line_raw is much faster
@@ -173,6 +254,18 @@ where
         spi: &mut dyn Transfer<u8, Error = SpiError>,
         s: &str,
         x_pos: i32,
+    ) -> Result<(), Error<SpiError, PinError>> {
+        self.write_str_at_pos_with_font(spi, s, x_pos, &CP437FONT)
+    }
+
+    /// Same as write_str_at_pos but draws the characters using any font, for example
+    /// a Styled wrapper around CP437FONT to get bold or inverse text
+    pub fn write_str_at_pos_with_font<SpiError, F: Font + ?Sized>(
+        &mut self,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+        s: &str,
+        x_pos: i32,
+        font: &F,
     ) -> Result<(), Error<SpiError, PinError>> {
         let string = s.as_bytes();
         let shift_by_bits = (x_pos % 8) as i8;
@@ -186,15 +279,20 @@ where
                 // write the string backwards because we push bytes onto the bus so the last
                 // character appears first
                 let string_index =
-                    self.num_devices as i32 - chip_index as i32 - 1 - start_string_index as i32;
+                    self.num_devices as i32 - chip_index as i32 - 1 - start_string_index;
                 let register = line_index as u8 + 1;
                 self.shift_out(spi, register)?;
 
                 // bit of a strange range check here but we need to draw the remainder of the last character
                 if string_index >= 0 && string_index <= string.len() as i32 {
                     // we may need to draw a single character over two chips so we need to do some bit shifting
-                    let val =
-                        self.get_byte_at(string, string_index as usize, line_index, shift_by_bits);
+                    let val = self.get_byte_at(
+                        font,
+                        string,
+                        string_index as usize,
+                        line_index,
+                        shift_by_bits,
+                    );
                     self.shift_out(spi, val)?;
                 } else {
                     self.shift_out(spi, 0)?;
@@ -211,8 +309,9 @@ where
     /// gets a byte representing part of a font character shifted by some number of bits
     /// it is possible to get part of the next or previous character returned because of the
     /// position shifting
-    fn get_byte_at(
+    fn get_byte_at<F: Font + ?Sized>(
         &mut self,
+        font: &F,
         string: &[u8],
         string_index: usize,
         line_index: usize,
@@ -224,19 +323,19 @@ where
         let len = string.len() as i32;
 
         let left = if is_in_range(len, left_index) {
-            CP437FONT[string[left_index as usize] as usize]
+            font.glyph(string[left_index as usize])
         } else {
-            CP437FONT[0]
+            [0; 8]
         };
         let middle = if is_in_range(len, mid_index as i32) {
-            CP437FONT[string[mid_index] as usize]
+            font.glyph(string[mid_index])
         } else {
-            CP437FONT[0]
+            [0; 8]
         };
         let right = if is_in_range(len, right_index as i32) {
-            CP437FONT[string[right_index] as usize]
+            font.glyph(string[right_index])
         } else {
-            CP437FONT[0]
+            [0; 8]
         };
 
         if shift_by_num_bits == 0 {
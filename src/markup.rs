@@ -0,0 +1,272 @@
+use crate::style::Style;
+
+/// A style that can be switched on and off in the middle of a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    /// {b}
+    Bold,
+    /// {i}
+    Italic,
+    /// {u}
+    Underline,
+    /// {inv}
+    Inverse,
+    /// {blink}
+    Blink,
+}
+
+/// A piece of a message written in the inline markup language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// plain text to draw
+    Text(&'a str),
+    /// a tag like {b} which switches an attribute on
+    Start(Attribute),
+    /// a tag like {/b} which switches an attribute off
+    End(Attribute),
+    /// {icon:heart} draws a single named symbol
+    Icon(&'a str),
+    /// {pause:500} stops a scrolling message for a number of milliseconds
+    Pause(u32),
+}
+
+/// Splits a message into tokens without allocating. Use {{ for a literal brace.
+/// Anything in braces that is not a known tag is drawn as text
+pub struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(markup: &'a str) -> Self {
+        Parser { rest: markup }
+    }
+}
+
+impl<'a> Iterator for Parser<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        if self.rest.starts_with("{{") {
+            let text = &self.rest[..1];
+            self.rest = &self.rest[2..];
+            return Some(Token::Text(text));
+        }
+
+        if self.rest.starts_with('{') {
+            if let Some(end) = self.rest.find('}') {
+                if let Some(token) = parse_tag(&self.rest[1..end]) {
+                    self.rest = &self.rest[end + 1..];
+                    return Some(token);
+                }
+            }
+
+            // not a tag so the brace is just text
+            let (text, rest) = self.rest.split_at(1);
+            self.rest = rest;
+            return Some(Token::Text(text));
+        }
+
+        let end = self.rest.find('{').unwrap_or(self.rest.len());
+        let (text, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(Token::Text(text))
+    }
+}
+
+fn parse_tag(tag: &str) -> Option<Token<'_>> {
+    if let Some(name) = tag.strip_prefix('/') {
+        return parse_attribute(name).map(Token::End);
+    }
+    if let Some(name) = tag.strip_prefix("icon:") {
+        return Some(Token::Icon(name));
+    }
+    if let Some(ms) = tag.strip_prefix("pause:") {
+        return ms.parse().ok().map(Token::Pause);
+    }
+    parse_attribute(tag).map(Token::Start)
+}
+
+fn parse_attribute(name: &str) -> Option<Attribute> {
+    match name {
+        "b" => Some(Attribute::Bold),
+        "i" => Some(Attribute::Italic),
+        "u" => Some(Attribute::Underline),
+        "inv" => Some(Attribute::Inverse),
+        "blink" => Some(Attribute::Blink),
+        _ => None,
+    }
+}
+
+/// Looks up the CP437 character code for an icon name used in {icon:name}
+pub fn icon(name: &str) -> Option<u8> {
+    let c = match name {
+        "smiley" => 0x01,
+        "heart" => 0x03,
+        "diamond" => 0x04,
+        "club" => 0x05,
+        "spade" => 0x06,
+        "note" => 0x0E,
+        "sun" => 0x0F,
+        "up" => 0x18,
+        "down" => 0x19,
+        "right" => 0x1A,
+        "left" => 0x1B,
+        "block" => 0xDB,
+        "degree" => 0xF8,
+        "check" => 0xFB,
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// Something the text renderer has to act on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Item {
+    /// a character cell with the styles active at that point in the message
+    Char { c: u8, style: Style, blink: bool },
+    /// a zero width pause in the message
+    Pause(u32),
+}
+
+/// Walks a message keeping track of which attributes are switched on
+pub struct Items<'a> {
+    parser: Parser<'a>,
+    text: &'a [u8],
+    style: Style,
+    blink: bool,
+}
+
+impl<'a> Items<'a> {
+    pub fn new(markup: &'a str) -> Self {
+        Items {
+            parser: Parser::new(markup),
+            text: &[],
+            style: Style::PLAIN,
+            blink: false,
+        }
+    }
+
+    fn set(&mut self, attribute: Attribute, on: bool) {
+        match attribute {
+            Attribute::Bold => self.style.bold = on,
+            Attribute::Italic => self.style.italic = on,
+            Attribute::Underline => self.style.underline = if on { Some(7) } else { None },
+            Attribute::Inverse => self.style.inverse = on,
+            Attribute::Blink => self.blink = on,
+        }
+    }
+}
+
+impl<'a> Iterator for Items<'a> {
+    type Item = Item;
+
+    fn next(&mut self) -> Option<Item> {
+        loop {
+            if let Some((&c, rest)) = self.text.split_first() {
+                self.text = rest;
+                return Some(Item::Char {
+                    c,
+                    style: self.style,
+                    blink: self.blink,
+                });
+            }
+
+            match self.parser.next()? {
+                Token::Text(text) => self.text = text.as_bytes(),
+                Token::Start(attribute) => self.set(attribute, true),
+                Token::End(attribute) => self.set(attribute, false),
+                Token::Icon(name) => {
+                    if let Some(c) = icon(name) {
+                        return Some(Item::Char {
+                            c,
+                            style: self.style,
+                            blink: self.blink,
+                        });
+                    }
+                }
+                Token::Pause(ms) => return Some(Item::Pause(ms)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_text_and_tags() {
+        let mut parser = Parser::new("a{b}bold{/b}{inv}");
+        assert_eq!(parser.next(), Some(Token::Text("a")));
+        assert_eq!(parser.next(), Some(Token::Start(Attribute::Bold)));
+        assert_eq!(parser.next(), Some(Token::Text("bold")));
+        assert_eq!(parser.next(), Some(Token::End(Attribute::Bold)));
+        assert_eq!(parser.next(), Some(Token::Start(Attribute::Inverse)));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn double_brace_is_a_literal_brace() {
+        let mut parser = Parser::new("{{b}x");
+        assert_eq!(parser.next(), Some(Token::Text("{")));
+        assert_eq!(parser.next(), Some(Token::Text("b}x")));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn unknown_and_unclosed_tags_are_text() {
+        let mut parser = Parser::new("{foo}{b");
+        assert_eq!(parser.next(), Some(Token::Text("{")));
+        assert_eq!(parser.next(), Some(Token::Text("foo}")));
+        assert_eq!(parser.next(), Some(Token::Text("{")));
+        assert_eq!(parser.next(), Some(Token::Text("b")));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn pause_needs_a_number() {
+        let mut parser = Parser::new("{pause:500}{pause:x}");
+        assert_eq!(parser.next(), Some(Token::Pause(500)));
+        assert_eq!(parser.next(), Some(Token::Text("{")));
+        assert_eq!(parser.next(), Some(Token::Text("pause:x}")));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn items_carry_the_active_style() {
+        let mut items = Items::new("{u}a{/u}{icon:heart}{icon:nope}{blink}b");
+        let underlined = Style {
+            underline: Some(7),
+            ..Style::PLAIN
+        };
+        assert_eq!(
+            items.next(),
+            Some(Item::Char {
+                c: b'a',
+                style: underlined,
+                blink: false
+            })
+        );
+        assert_eq!(
+            items.next(),
+            Some(Item::Char {
+                c: 0x03,
+                style: Style::PLAIN,
+                blink: false
+            })
+        );
+        assert_eq!(
+            items.next(),
+            Some(Item::Char {
+                c: b'b',
+                style: Style::PLAIN,
+                blink: true
+            })
+        );
+        assert_eq!(items.next(), None);
+    }
+}
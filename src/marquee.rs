@@ -0,0 +1,237 @@
+use crate::canvas::Canvas;
+use crate::font::Font;
+use crate::markup::{Item, Items};
+use crate::text::{draw_markup, markup_width, Direction, CHAR_WIDTH};
+
+/// Most steps a single tick will take to catch up after being called late
+const MAX_CATCH_UP_STEPS: u32 = 64;
+
+/// Scrolls a message written in the inline markup language across the display, one pixel per
+/// step. Call tick with a millisecond timestamp as often as you like and draw whenever it
+/// returns true. A {pause:ms} tag holds the message still once the text following the tag
+/// reaches the edge of the display the message scrolls towards.
+/// Left to right and top to bottom text scrolls towards device 0 while right to left text
+/// scrolls the opposite way
+pub struct Marquee<'a> {
+    markup: &'a str,
+    direction: Direction,
+    display_width: i32,
+    x: i32,
+    step_ms: u32,
+    blink_ms: u32,
+    last_step_ms: Option<u32>,
+    hold_ms: u32,
+    blink_on: bool,
+}
+
+impl<'a> Marquee<'a> {
+    /// display_width is the width of the canvas the marquee is drawn onto in pixels
+    pub fn new(markup: &'a str, display_width: i32, step_ms: u32) -> Self {
+        Marquee {
+            markup,
+            direction: Direction::LeftToRight,
+            display_width,
+            x: display_width,
+            step_ms,
+            blink_ms: 500,
+            last_step_ms: None,
+            hold_ms: 0,
+            blink_on: true,
+        }
+    }
+
+    /// Sets how long blinking text stays on and off for (500ms by default)
+    pub fn set_blink_period(&mut self, blink_ms: u32) {
+        self.blink_ms = blink_ms;
+    }
+
+    /// Replaces the message and starts scrolling it in from the edge of the display
+    pub fn set_markup(&mut self, markup: &'a str) {
+        self.markup = markup;
+        self.restart();
+    }
+
+    /// Changes the text direction and starts scrolling the message in again
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+        self.restart();
+    }
+
+    /// The x position the message is drawn at, see Direction for what this means
+    pub fn position(&self) -> i32 {
+        self.x
+    }
+
+    /// Advances the marquee, returns true if it needs to be redrawn
+    pub fn tick(&mut self, now_ms: u32) -> bool {
+        let blink_on = self.blink_ms == 0 || (now_ms / self.blink_ms) & 1 == 0;
+        let blink_changed = blink_on != self.blink_on;
+        self.blink_on = blink_on;
+
+        // the first tick only starts the clock so the message still scrolls in from the edge
+        let mut last_step_ms = match self.last_step_ms {
+            Some(last_step_ms) => last_step_ms,
+            None => {
+                self.last_step_ms = Some(now_ms);
+                return true;
+            }
+        };
+
+        // take every step that is due and keep the leftover time so the speed does not depend
+        // on how often tick is called. A step_ms of 0 moves once per tick
+        let width = markup_width(self.markup);
+        let max_steps = if self.step_ms == 0 {
+            1
+        } else {
+            MAX_CATCH_UP_STEPS
+        };
+        let mut moved = false;
+        for _ in 0..max_steps {
+            let due = self.step_ms.saturating_add(self.hold_ms);
+            if now_ms.wrapping_sub(last_step_ms) < due {
+                self.last_step_ms = Some(last_step_ms);
+                return blink_changed || moved;
+            }
+            last_step_ms = last_step_ms.wrapping_add(due);
+            self.step(width);
+            moved = true;
+        }
+
+        // too far behind to catch up so time from now
+        self.last_step_ms = Some(now_ms);
+        true
+    }
+
+    /// Clears the canvas and draws the message at its current position
+    pub fn draw<C: Canvas + ?Sized, F: Font + ?Sized>(&self, canvas: &mut C, font: &F) {
+        canvas.clear();
+        draw_markup(
+            canvas,
+            font,
+            self.markup,
+            self.x,
+            0,
+            self.direction,
+            self.blink_on,
+        );
+    }
+
+    fn step(&mut self, width: i32) {
+        if self.direction == Direction::RightToLeft {
+            self.x += 1;
+            if self.x - width > self.display_width {
+                self.x = 0;
+            }
+        } else {
+            self.x -= 1;
+            if self.x < -width {
+                self.x = self.display_width;
+            }
+        }
+        self.hold_ms = self.pause_at_edge();
+    }
+
+    fn restart(&mut self) {
+        self.x = if self.direction == Direction::RightToLeft {
+            0
+        } else {
+            self.display_width
+        };
+        self.hold_ms = 0;
+        self.last_step_ms = None;
+    }
+
+    fn pause_at_edge(&self) -> u32 {
+        let mut offset = 0;
+        for item in Items::new(self.markup) {
+            match item {
+                Item::Char { .. } => offset += CHAR_WIDTH,
+                Item::Pause(ms) if self.is_at_edge(offset) => return ms,
+                Item::Pause(_) => {}
+            }
+        }
+        0
+    }
+
+    fn is_at_edge(&self, offset: i32) -> bool {
+        if self.direction == Direction::RightToLeft {
+            self.x - offset == self.display_width
+        } else {
+            self.x + offset == 0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_does_not_depend_on_how_often_tick_is_called() {
+        let mut marquee = Marquee::new("hello", 32, 20);
+        marquee.tick(1000);
+        for now_ms in (1050..=1200).step_by(50) {
+            assert!(marquee.tick(now_ms));
+        }
+        assert_eq!(marquee.position(), 32 - 10);
+
+        // a tick between steps keeps the leftover time
+        assert!(!marquee.tick(1219));
+        assert!(marquee.tick(1220));
+        assert_eq!(marquee.position(), 32 - 11);
+    }
+
+    #[test]
+    fn first_tick_starts_the_clock() {
+        let mut marquee = Marquee::new("hello", 32, 20);
+        assert!(marquee.tick(5000));
+        assert_eq!(marquee.position(), 32);
+        marquee.tick(5020);
+        assert_eq!(marquee.position(), 31);
+
+        // a new message scrolls in from the edge again
+        marquee.set_markup("bye");
+        marquee.tick(90_000);
+        assert_eq!(marquee.position(), 32);
+        marquee.tick(90_040);
+        assert_eq!(marquee.position(), 30);
+    }
+
+    #[test]
+    fn pause_holds_the_message() {
+        let mut marquee = Marquee::new("{pause:100}hi", 2, 10);
+        marquee.tick(0);
+        marquee.tick(20);
+        assert_eq!(marquee.position(), 0);
+        // held for the pause on top of the normal step
+        assert!(!marquee.tick(120));
+        assert!(marquee.tick(130));
+        assert_eq!(marquee.position(), -1);
+    }
+
+    #[test]
+    fn right_to_left_scrolls_right_and_wraps() {
+        let mut marquee = Marquee::new("ab", 8, 10);
+        marquee.set_direction(Direction::RightToLeft);
+        assert_eq!(marquee.position(), 0);
+        marquee.tick(0);
+        marquee.tick(240);
+        // the message has just gone off the right hand edge
+        assert_eq!(marquee.position(), 24);
+        marquee.tick(250);
+        assert_eq!(marquee.position(), 0);
+    }
+
+    #[test]
+    fn right_to_left_pauses_at_the_right_hand_edge() {
+        let mut marquee = Marquee::new("a{pause:100}b", 8, 10);
+        marquee.set_direction(Direction::RightToLeft);
+        marquee.tick(0);
+        marquee.tick(160);
+        // b has reached the right hand edge with a gone off past it
+        assert_eq!(marquee.position(), 16);
+        assert!(!marquee.tick(269));
+        assert!(marquee.tick(270));
+        assert_eq!(marquee.position(), 17);
+    }
+}
@@ -0,0 +1,62 @@
+//! Stand ins for the spi bus and chip select pin so tests can check what is sent to the chips
+use core::convert::Infallible;
+use embedded_hal::{blocking::spi::Transfer, digital::v2::OutputPin};
+
+/// A chip select pin that does nothing
+pub struct Pin;
+
+impl OutputPin for Pin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// Records every byte sent. With a chain of one chip each pair of bytes is a register and
+/// the data written to it
+pub struct Spi {
+    bytes: [u8; 1024],
+    len: usize,
+}
+
+impl Spi {
+    pub fn new() -> Self {
+        Spi {
+            bytes: [0; 1024],
+            len: 0,
+        }
+    }
+
+    /// The register and data pairs sent so far
+    pub fn commands(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        self.bytes[..self.len]
+            .chunks(2)
+            .map(|pair| (pair[0], pair[1]))
+    }
+
+    /// Where a write to a register first shows up in commands
+    pub fn position(&self, register: u8) -> Option<usize> {
+        self.commands().position(|(r, _)| r == register)
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Transfer<u8> for Spi {
+    type Error = Infallible;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Infallible> {
+        for word in words.iter() {
+            self.bytes[self.len] = *word;
+            self.len += 1;
+        }
+        Ok(words)
+    }
+}
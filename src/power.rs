@@ -0,0 +1,263 @@
+use crate::framebuffer::FrameBuffer;
+use crate::{Command, Error, MAX7219};
+use embedded_hal::{blocking::spi::Transfer, digital::v2::OutputPin};
+
+/// Estimates how much current a chain draws. Each chip scans its rows (digits) one at a time so
+/// a lit led is only driven while its row is selected, and the Intensity register further
+/// limits that to (2 * intensity + 1) / 32 of the time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerModel {
+    /// peak current through a single lit led, set by the RSET resistor (about 40mA with 9.53k)
+    pub segment_ma: u32,
+    /// current each chip draws with every led off (about 8mA when not shut down)
+    pub device_ma: u32,
+}
+
+impl Default for PowerModel {
+    fn default() -> Self {
+        PowerModel {
+            segment_ma: 40,
+            device_ma: 8,
+        }
+    }
+}
+
+impl PowerModel {
+    /// Average current in milliamps (rounded up) for a frame at an intensity and scan limit.
+    /// Only rows up to and including the scan limit are lit
+    pub fn estimate_ma<const N: usize>(
+        &self,
+        framebuffer: &FrameBuffer<N>,
+        intensity: u8,
+        scan_limit: u8,
+    ) -> u32 {
+        let scan_limit = scan_limit.min(7) as usize;
+        let lit: u32 = (0..N)
+            .map(|device| {
+                framebuffer.device(device)[..=scan_limit]
+                    .iter()
+                    .map(|row| row.count_ones())
+                    .sum::<u32>()
+            })
+            .sum();
+        let duty_32nds = 2 * intensity.min(15) as u32 + 1;
+        let rows = scan_limit as u32 + 1;
+        let leds_ua =
+            lit as u64 * self.segment_ma as u64 * 1000 * duty_32nds as u64 / (32 * rows as u64);
+        N as u32 * self.device_ma + ((leds_ua + 999) / 1000) as u32
+    }
+
+    /// The highest intensity up to wanted that keeps a frame within budget_ma,
+    /// None if even the lowest intensity is over budget
+    pub fn max_intensity<const N: usize>(
+        &self,
+        framebuffer: &FrameBuffer<N>,
+        wanted: u8,
+        scan_limit: u8,
+        budget_ma: u32,
+    ) -> Option<u8> {
+        (0..=wanted.min(15))
+            .rev()
+            .find(|intensity| self.estimate_ma(framebuffer, *intensity, scan_limit) <= budget_ma)
+    }
+}
+
+/// What to do with a frame that would go over the power budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// lower the intensity until the frame fits
+    CapBrightness,
+    /// don't send the frame at all
+    Refuse,
+}
+
+/// Sends frames to the chips without ever going over a current budget, for example to keep a
+/// long chain from browning out a USB supply. The limiter owns the Intensity register so set
+/// the brightness you would like with set_intensity rather than writing the command directly
+pub struct PowerLimiter {
+    model: PowerModel,
+    budget_ma: u32,
+    policy: Policy,
+    scan_limit: u8,
+    intensity: u8,
+    sent_intensity: Option<u8>,
+}
+
+impl PowerLimiter {
+    pub fn new(model: PowerModel, budget_ma: u32, policy: Policy) -> Self {
+        PowerLimiter {
+            model,
+            budget_ma,
+            policy,
+            scan_limit: 7,
+            intensity: 15,
+            sent_intensity: None,
+        }
+    }
+
+    /// The intensity used when the frame is within budget (15 by default)
+    pub fn set_intensity(&mut self, intensity: u8) {
+        self.intensity = intensity.min(15);
+    }
+
+    /// The scan limit the chips have been configured with (7 by default)
+    pub fn set_scan_limit(&mut self, scan_limit: u8) {
+        self.scan_limit = scan_limit.min(7);
+    }
+
+    /// The intensity last sent to the chips
+    pub fn sent_intensity(&self) -> Option<u8> {
+        self.sent_intensity
+    }
+
+    /// Estimated current of a frame at the requested intensity
+    pub fn estimate_ma<const N: usize>(&self, framebuffer: &FrameBuffer<N>) -> u32 {
+        self.model
+            .estimate_ma(framebuffer, self.intensity, self.scan_limit)
+    }
+
+    /// Sends a frame, lowering the intensity first if it would go over budget (or refusing to
+    /// send it, depending on the policy). Returns the intensity the frame is shown at or None if
+    /// the frame was refused, which also happens when it is over budget even at intensity 0
+    pub fn write_framebuffer<CS, PinError, SpiError, const N: usize>(
+        &mut self,
+        max7219: &mut MAX7219<CS>,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+        framebuffer: &FrameBuffer<N>,
+    ) -> Result<Option<u8>, Error<SpiError, PinError>>
+    where
+        CS: OutputPin<Error = PinError>,
+    {
+        let allowed =
+            self.model
+                .max_intensity(framebuffer, self.intensity, self.scan_limit, self.budget_ma);
+        let intensity = match (allowed, self.policy) {
+            (Some(intensity), Policy::CapBrightness) => intensity,
+            (Some(intensity), Policy::Refuse) if intensity == self.intensity => intensity,
+            _ => return Ok(None),
+        };
+
+        // dim before showing a heavier frame and brighten only after a lighter one is showing
+        // so the budget is not exceeded in between
+        let dimming = self.sent_intensity.map_or(true, |sent| intensity < sent);
+        if dimming {
+            self.send_intensity(max7219, spi, intensity)?;
+        }
+        max7219.write_framebuffer(spi, framebuffer)?;
+        if !dimming {
+            self.send_intensity(max7219, spi, intensity)?;
+        }
+        Ok(Some(intensity))
+    }
+
+    fn send_intensity<CS, PinError, SpiError>(
+        &mut self,
+        max7219: &mut MAX7219<CS>,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+        intensity: u8,
+    ) -> Result<(), Error<SpiError, PinError>>
+    where
+        CS: OutputPin<Error = PinError>,
+    {
+        if self.sent_intensity != Some(intensity) {
+            max7219.write_command_all(spi, Command::Intensity, intensity)?;
+            self.sent_intensity = Some(intensity);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Pin, Spi};
+
+    const DIGIT0: u8 = Command::Digit0 as u8;
+    const INTENSITY: u8 = Command::Intensity as u8;
+
+    fn full() -> FrameBuffer<1> {
+        let mut fb = FrameBuffer::<1>::new();
+        *fb.device_mut(0) = [0xFF; 8];
+        fb
+    }
+
+    #[test]
+    fn estimate_follows_lit_leds_and_duty() {
+        let model = PowerModel::default();
+        let blank = FrameBuffer::<2>::new();
+        assert_eq!(model.estimate_ma(&blank, 15, 7), 16);
+
+        // 64 leds at 40mA each lit for 1/8 of the scan and 31/32 of that
+        let mut fb = FrameBuffer::<2>::new();
+        *fb.device_mut(0) = [0xFF; 8];
+        assert_eq!(model.estimate_ma(&fb, 15, 7), 16 + 310);
+        assert_eq!(model.estimate_ma(&fb, 0, 7), 16 + 10);
+
+        // rows past the scan limit are not lit, the rest are lit for longer
+        *fb.device_mut(0) = [0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF];
+        assert_eq!(model.estimate_ma(&fb, 15, 3), 16);
+        *fb.device_mut(0) = [0xFF, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(model.estimate_ma(&fb, 15, 3), 16 + 78);
+    }
+
+    #[test]
+    fn max_intensity_is_none_when_nothing_fits() {
+        let model = PowerModel::default();
+        assert_eq!(model.max_intensity(&full(), 15, 7, 1000), Some(15));
+        assert_eq!(model.max_intensity(&full(), 15, 7, 8 + 100), Some(4));
+        assert_eq!(model.max_intensity(&full(), 15, 7, 8 + 9), None);
+        // the chip on its own is already over budget
+        assert_eq!(
+            model.max_intensity(&FrameBuffer::<1>::new(), 15, 7, 7),
+            None
+        );
+    }
+
+    #[test]
+    fn refused_frames_are_not_sent() {
+        let (mut pin, mut spi) = (Pin, Spi::new());
+        let mut max7219 = MAX7219::new(&mut pin, 1);
+        let mut limiter = PowerLimiter::new(PowerModel::default(), 100, Policy::Refuse);
+        let sent = limiter.write_framebuffer(&mut max7219, &mut spi, &full());
+        assert_eq!(sent.unwrap(), None);
+        assert_eq!(spi.commands().count(), 0);
+
+        let sent = limiter.write_framebuffer(&mut max7219, &mut spi, &FrameBuffer::<1>::new());
+        assert_eq!(sent.unwrap(), Some(15));
+    }
+
+    #[test]
+    fn dims_before_and_brightens_after() {
+        let (mut pin, mut spi) = (Pin, Spi::new());
+        let mut max7219 = MAX7219::new(&mut pin, 1);
+        let mut limiter = PowerLimiter::new(PowerModel::default(), 8 + 100, Policy::CapBrightness);
+        let blank = FrameBuffer::<1>::new();
+        assert_eq!(
+            limiter
+                .write_framebuffer(&mut max7219, &mut spi, &blank)
+                .unwrap(),
+            Some(15)
+        );
+
+        // a heavier frame dims first
+        spi.clear();
+        let sent = limiter.write_framebuffer(&mut max7219, &mut spi, &full());
+        assert_eq!(sent.unwrap(), Some(4));
+        assert!(spi.position(INTENSITY) < spi.position(DIGIT0));
+        assert!(spi.commands().any(|command| command == (INTENSITY, 4)));
+
+        // a lighter frame is shown before brightening
+        spi.clear();
+        let sent = limiter.write_framebuffer(&mut max7219, &mut spi, &blank);
+        assert_eq!(sent.unwrap(), Some(15));
+        assert!(spi.position(DIGIT0) < spi.position(INTENSITY));
+        assert!(spi.commands().any(|command| command == (INTENSITY, 15)));
+
+        // nothing changes so the intensity is not sent again
+        spi.clear();
+        limiter
+            .write_framebuffer(&mut max7219, &mut spi, &blank)
+            .unwrap();
+        assert_eq!(spi.position(INTENSITY), None);
+    }
+}
@@ -1,4 +1,4 @@
-fn is_bit_set (byte : u8, n : u8) -> bool {
+fn is_bit_set(byte: u8, n: usize) -> bool {
     if n < 8 {
         byte & (1 << n) != 0
     } else {
@@ -8,17 +8,33 @@ fn is_bit_set (byte : u8, n : u8) -> bool {
 
 /// Rotate an 8x8 matrix by 90 degrees clockwise
 /// used in this project for rotating letters in a font file
-pub fn rotate_90_clockwise (buffer: [u8; 8]) -> [u8; 8]{
+pub fn rotate_90_clockwise(buffer: [u8; 8]) -> [u8; 8] {
     let mut rotated: [u8; 8] = [0; 8];
 
     for (i, line) in buffer.iter().enumerate() {
         for j in 0..8 {
             if is_bit_set(*line, j) {
                 let mask: u8 = 1 << i as u8;
-                rotated[7 - j] = rotated[7 - j] | mask;
+                rotated[7 - j] |= mask;
             }
         }
     }
 
     rotated
-}
\ No newline at end of file
+}
+
+/// Rotate an 8x8 matrix by 90 degrees counter clockwise
+pub fn rotate_90_counter_clockwise(buffer: [u8; 8]) -> [u8; 8] {
+    let mut rotated: [u8; 8] = [0; 8];
+
+    for (i, line) in buffer.iter().enumerate() {
+        for (j, rotated_line) in rotated.iter_mut().enumerate() {
+            if is_bit_set(*line, j) {
+                let mask: u8 = 1 << (7 - i) as u8;
+                *rotated_line |= mask;
+            }
+        }
+    }
+
+    rotated
+}
@@ -0,0 +1,98 @@
+use crate::bitmap::Bitmap;
+use crate::canvas::{Canvas, Rect};
+
+/// The way pixels move when scrolling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Moves every pixel inside area one pixel in a direction, carrying pixels across device
+/// boundaries. The pixels that fall off the edge are lost and the column or row that opens up
+/// on the other side is filled in by calling fill with the y position (for left and right) or
+/// the x position (for up and down) of each pixel, relative to the area
+pub fn scroll<C: Canvas + ?Sized>(
+    canvas: &mut C,
+    area: Rect,
+    direction: ScrollDirection,
+    mut fill: impl FnMut(i32) -> bool,
+) {
+    let area = area.intersect(&Rect::new(0, 0, canvas.width(), canvas.height()));
+    if area.is_empty() {
+        return;
+    }
+    let (left, top, right, bottom) = (area.x, area.y, area.right() - 1, area.bottom() - 1);
+
+    match direction {
+        ScrollDirection::Left => {
+            for y in top..=bottom {
+                for x in left..right {
+                    let on = canvas.pixel(x + 1, y);
+                    canvas.set_pixel(x, y, on);
+                }
+                canvas.set_pixel(right, y, fill(y - top));
+            }
+        }
+        ScrollDirection::Right => {
+            for y in top..=bottom {
+                for x in (left + 1..=right).rev() {
+                    let on = canvas.pixel(x - 1, y);
+                    canvas.set_pixel(x, y, on);
+                }
+                canvas.set_pixel(left, y, fill(y - top));
+            }
+        }
+        ScrollDirection::Up => {
+            for x in left..=right {
+                for y in top..bottom {
+                    let on = canvas.pixel(x, y + 1);
+                    canvas.set_pixel(x, y, on);
+                }
+                canvas.set_pixel(x, bottom, fill(x - left));
+            }
+        }
+        ScrollDirection::Down => {
+            for x in left..=right {
+                for y in (top + 1..=bottom).rev() {
+                    let on = canvas.pixel(x, y - 1);
+                    canvas.set_pixel(x, y, on);
+                }
+                canvas.set_pixel(x, top, fill(x - left));
+            }
+        }
+    }
+}
+
+/// Scrolls area one pixel and feeds in the next column or row of a bitmap so that it slides
+/// into view, for example a logo or a graph. step counts the pixels scrolled so far and wraps
+/// around so the bitmap repeats
+pub fn scroll_bitmap<C: Canvas + ?Sized>(
+    canvas: &mut C,
+    area: Rect,
+    direction: ScrollDirection,
+    bitmap: &Bitmap,
+    step: i32,
+) {
+    let (width, height) = (bitmap.width().max(1), bitmap.height().max(1));
+    match direction {
+        ScrollDirection::Left => {
+            let column = step.rem_euclid(width);
+            scroll(canvas, area, direction, |y| bitmap.pixel(column, y));
+        }
+        ScrollDirection::Right => {
+            let column = width - 1 - step.rem_euclid(width);
+            scroll(canvas, area, direction, |y| bitmap.pixel(column, y));
+        }
+        ScrollDirection::Up => {
+            let row = step.rem_euclid(height);
+            scroll(canvas, area, direction, |x| bitmap.pixel(x, row));
+        }
+        ScrollDirection::Down => {
+            let row = height - 1 - step.rem_euclid(height);
+            scroll(canvas, area, direction, |x| bitmap.pixel(x, row));
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::bitmap::{blit, Bitmap, BlendMode};
+use crate::canvas::{Canvas, Point, Rect};
+
+/// Most movement steps or frame changes made in a single update when catching up after a
+/// long gap
+const MAX_CATCH_UP_STEPS: u32 = 64;
+
+/// How a sprite moves, one step every step interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Path<'a> {
+    /// stays where it is
+    Still,
+    /// moves dx, dy pixels every step
+    Line { dx: i32, dy: i32 },
+    /// moves dx, dy pixels every step and bounces off the sides of a box
+    Bounce { dx: i32, dy: i32, bounds: Rect },
+    /// moves one pixel per step towards each point in turn then starts again at the first one
+    Waypoints(&'a [Point]),
+}
+
+/// The edges of the canvas a sprite is touching or has gone past
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Edges {
+    pub left: bool,
+    pub right: bool,
+    pub top: bool,
+    pub bottom: bool,
+}
+
+impl Edges {
+    pub fn any(&self) -> bool {
+        self.left || self.right || self.top || self.bottom
+    }
+}
+
+/// An animated image made of several bitmap frames, each shown for its own duration,
+/// which moves along a path
+pub struct Sprite<'a> {
+    frames: &'a [Bitmap<'a>],
+    durations_ms: &'a [u32],
+    path: Path<'a>,
+    step_ms: u32,
+    mode: BlendMode,
+    position: Point,
+    waypoint: usize,
+    frame: usize,
+    frame_start_ms: Option<u32>,
+    last_step_ms: Option<u32>,
+}
+
+impl<'a> Sprite<'a> {
+    /// durations_ms gives how long each frame is shown for, the last duration is used for any
+    /// frames without one. The sprite moves one step along its path every step_ms
+    pub fn new(
+        frames: &'a [Bitmap<'a>],
+        durations_ms: &'a [u32],
+        position: Point,
+        path: Path<'a>,
+        step_ms: u32,
+    ) -> Self {
+        Sprite {
+            frames,
+            durations_ms,
+            path,
+            step_ms,
+            mode: BlendMode::Or,
+            position,
+            waypoint: 0,
+            frame: 0,
+            frame_start_ms: None,
+            last_step_ms: None,
+        }
+    }
+
+    /// How the sprite is combined with what is underneath it (Or by default)
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.mode = mode;
+    }
+
+    pub fn set_path(&mut self, path: Path<'a>) {
+        self.path = path;
+        self.waypoint = 0;
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    /// The area the current frame covers
+    pub fn bounds(&self) -> Rect {
+        match self.frames.get(self.frame) {
+            Some(bitmap) => Rect::new(
+                self.position.x,
+                self.position.y,
+                bitmap.width(),
+                bitmap.height(),
+            ),
+            None => Rect::new(self.position.x, self.position.y, 0, 0),
+        }
+    }
+
+    /// Advances the animation frame and moves the sprite, returns true if it needs redrawing
+    pub fn update(&mut self, now_ms: u32) -> bool {
+        let mut changed = false;
+
+        // both clocks move on by whole frames and steps rather than to now_ms so the leftover
+        // time is kept and the speed does not depend on how often update is called
+        let mut frame_start = *self.frame_start_ms.get_or_insert(now_ms);
+        if self.frames.len() > 1 {
+            for _ in 0..MAX_CATCH_UP_STEPS {
+                let duration = self.duration(self.frame);
+                if now_ms.wrapping_sub(frame_start) < duration {
+                    break;
+                }
+                frame_start = frame_start.wrapping_add(duration);
+                self.frame = (self.frame + 1) % self.frames.len();
+                changed = true;
+            }
+            if now_ms.wrapping_sub(frame_start) >= self.duration(self.frame) {
+                // too far behind to catch up so time the current frame from now
+                frame_start = now_ms;
+            }
+            self.frame_start_ms = Some(frame_start);
+        }
+
+        let last_step = *self.last_step_ms.get_or_insert(now_ms);
+        let (steps, last_step) = match self.step_ms {
+            0 => (1, now_ms),
+            step_ms => {
+                let due = now_ms.wrapping_sub(last_step) / step_ms;
+                if due > MAX_CATCH_UP_STEPS {
+                    (MAX_CATCH_UP_STEPS, now_ms)
+                } else {
+                    (due, last_step.wrapping_add(due * step_ms))
+                }
+            }
+        };
+        self.last_step_ms = Some(last_step);
+        for _ in 0..steps {
+            changed |= self.step();
+        }
+        changed
+    }
+
+    /// The canvas edges the sprite is touching or has crossed
+    pub fn collisions(&self, width: i32, height: i32) -> Edges {
+        let bounds = self.bounds();
+        Edges {
+            left: bounds.x <= 0,
+            right: bounds.right() >= width,
+            top: bounds.y <= 0,
+            bottom: bounds.bottom() >= height,
+        }
+    }
+
+    /// Draws the current frame, clipped to the canvas
+    pub fn draw<C: Canvas + ?Sized>(&self, canvas: &mut C) {
+        if let Some(bitmap) = self.frames.get(self.frame) {
+            blit(
+                canvas,
+                bitmap,
+                self.position.x,
+                self.position.y,
+                self.mode,
+                None,
+            );
+        }
+    }
+
+    fn duration(&self, frame: usize) -> u32 {
+        self.durations_ms
+            .get(frame)
+            .or_else(|| self.durations_ms.last())
+            .copied()
+            .unwrap_or(u32::MAX)
+    }
+
+    /// Moves one step along the path, returns true if the sprite moved
+    fn step(&mut self) -> bool {
+        let before = self.position;
+        match self.path {
+            Path::Still => {}
+            Path::Line { dx, dy } => {
+                self.position.x += dx;
+                self.position.y += dy;
+            }
+            Path::Bounce { dx, dy, bounds } => {
+                let size = self.bounds();
+                let (x, dx) = bounce(self.position.x, dx, bounds.x, bounds.right() - size.width);
+                let (y, dy) = bounce(self.position.y, dy, bounds.y, bounds.bottom() - size.height);
+                self.position = Point::new(x, y);
+                self.path = Path::Bounce { dx, dy, bounds };
+            }
+            Path::Waypoints(points) => {
+                if let Some(target) = points.get(self.waypoint) {
+                    self.position.x += (target.x - self.position.x).signum();
+                    self.position.y += (target.y - self.position.y).signum();
+                    if self.position == *target {
+                        self.waypoint = (self.waypoint + 1) % points.len();
+                    }
+                }
+            }
+        }
+        self.position != before
+    }
+}
+
+/// Moves along one axis between min and max, reversing direction at either end
+fn bounce(position: i32, speed: i32, min: i32, max: i32) -> (i32, i32) {
+    let next = position + speed;
+    let (next, speed) = if next < min {
+        (2 * min - next, -speed)
+    } else if next > max {
+        (2 * max - next, -speed)
+    } else {
+        (next, speed)
+    };
+    (next.clamp(min, max.max(min)), speed)
+}
+
+/// Updates every sprite, returns true if any of them need redrawing
+pub fn update_sprites(sprites: &mut [Sprite], now_ms: u32) -> bool {
+    sprites
+        .iter_mut()
+        .fold(false, |changed, sprite| sprite.update(now_ms) | changed)
+}
+
+/// Draws every sprite in order so later sprites appear on top
+pub fn draw_sprites<C: Canvas + ?Sized>(sprites: &[Sprite], canvas: &mut C) {
+    for sprite in sprites {
+        sprite.draw(canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOT: [u8; 1] = [0x80];
+    const FRAMES: [Bitmap; 2] = [Bitmap::new(1, 1, &DOT), Bitmap::new(1, 1, &DOT)];
+
+    #[test]
+    fn speed_does_not_depend_on_how_often_update_is_called() {
+        let path = Path::Line { dx: 1, dy: 0 };
+        let mut sprite = Sprite::new(&FRAMES, &[100], Point::new(0, 0), path, 100);
+        sprite.update(0);
+        for now_ms in [150, 300, 450] {
+            assert!(sprite.update(now_ms));
+        }
+        assert_eq!(sprite.position(), Point::new(4, 0));
+        assert_eq!(sprite.frame, 0);
+
+        // leftover time is kept between updates
+        assert!(!sprite.update(499));
+        assert!(sprite.update(500));
+        assert_eq!(sprite.position(), Point::new(5, 0));
+        assert_eq!(sprite.frame, 1);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::font::Font;
+
+/// Render-time text styles which can be applied to the glyphs of any font.
+/// Rows are counted from the top of the character cell so row 7 is the bottom row
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    /// thicken strokes by OR-ing the glyph with itself shifted one column to the right
+    pub bold: bool,
+    /// shear the top half of the glyph one column to the right
+    pub italic: bool,
+    /// row to light up underneath the text (usually 7), None for no underline
+    pub underline: Option<u8>,
+    /// flip every pixel in the character cell
+    pub inverse: bool,
+}
+
+impl Style {
+    /// No styling at all, glyphs are drawn exactly as they appear in the font
+    pub const PLAIN: Style = Style {
+        bold: false,
+        italic: false,
+        underline: None,
+        inverse: false,
+    };
+
+    /// Returns a copy of the glyph with all the style flags applied
+    pub fn apply(&self, glyph: [u8; 8]) -> [u8; 8] {
+        let mut styled = glyph;
+
+        // glyph rows start at the bottom so the top half is rows 4 to 7
+        if self.italic {
+            for row in styled.iter_mut().skip(4) {
+                *row <<= 1;
+            }
+        }
+
+        if self.bold {
+            for row in styled.iter_mut() {
+                *row |= *row << 1;
+            }
+        }
+
+        if let Some(underline) = self.underline {
+            if underline < 8 {
+                styled[7 - underline as usize] = 0xFF;
+            }
+        }
+
+        if self.inverse {
+            for row in styled.iter_mut() {
+                *row = !*row;
+            }
+        }
+
+        styled
+    }
+}
+
+/// Wraps a font so that every glyph it returns has a style applied to it
+pub struct Styled<F> {
+    pub font: F,
+    pub style: Style,
+}
+
+impl<F: Font> Styled<F> {
+    pub fn new(font: F, style: Style) -> Self {
+        Styled { font, style }
+    }
+}
+
+impl<F: Font> Font for Styled<F> {
+    fn glyph(&self, c: u8) -> [u8; 8] {
+        self.style.apply(self.font.glyph(c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // bit 0 is the leftmost pixel and index 0 the bottom row
+    const GLYPH: [u8; 8] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x00];
+
+    #[test]
+    fn plain_leaves_the_glyph_alone() {
+        assert_eq!(Style::PLAIN.apply(GLYPH), GLYPH);
+    }
+
+    #[test]
+    fn bold_adds_a_column_to_the_right() {
+        let style = Style {
+            bold: true,
+            ..Style::PLAIN
+        };
+        assert_eq!(
+            style.apply(GLYPH),
+            [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xC0, 0x00]
+        );
+        // the rightmost column has nowhere to go
+        assert_eq!(style.apply([0x80; 8]), [0x80; 8]);
+    }
+
+    #[test]
+    fn italic_only_shears_the_top_half() {
+        let style = Style {
+            italic: true,
+            ..Style::PLAIN
+        };
+        assert_eq!(
+            style.apply(GLYPH),
+            [0x01, 0x02, 0x04, 0x08, 0x20, 0x40, 0x80, 0x00]
+        );
+    }
+
+    #[test]
+    fn underline_rows_count_from_the_top() {
+        let underline = |row| {
+            Style {
+                underline: Some(row),
+                ..Style::PLAIN
+            }
+            .apply([0; 8])
+        };
+        assert_eq!(underline(7), [0xFF, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(underline(0), [0, 0, 0, 0, 0, 0, 0, 0xFF]);
+        assert_eq!(underline(8), [0; 8]);
+        assert_eq!(underline(255), [0; 8]);
+    }
+
+    #[test]
+    fn inverse_flips_the_underline_too() {
+        let style = Style {
+            underline: Some(7),
+            inverse: true,
+            ..Style::PLAIN
+        };
+        let styled = style.apply(GLYPH);
+        assert_eq!(styled[0], 0x00);
+        for row in 1..8 {
+            assert_eq!(styled[row], !GLYPH[row], "row {}", row);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::canvas::Canvas;
+use crate::font::Font;
+use crate::markup::{Item, Items};
+use crate::rotate::rotate_90_counter_clockwise;
+
+/// Width of a character cell in pixels
+pub const CHAR_WIDTH: i32 = 8;
+
+/// The way the characters of a message flow across the display
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// characters flow to the right of x
+    #[default]
+    LeftToRight,
+    /// characters flow to the left of x so x is the right hand edge of the first character
+    RightToLeft,
+    /// for a chain mounted in portrait with device 0 at the top. Each glyph is rotated a
+    /// quarter turn so it reads upright and the characters are stacked down the chain
+    TopToBottom,
+}
+
+impl Direction {
+    /// x position of the left edge of a character cell given the x position the text starts at
+    fn cell_x(self, x: i32, cell_index: i32) -> i32 {
+        match self {
+            Direction::RightToLeft => x - (cell_index + 1) * CHAR_WIDTH,
+            _ => x + cell_index * CHAR_WIDTH,
+        }
+    }
+
+    /// x position the next piece of text would start at after num_cells characters
+    fn advance(self, x: i32, num_cells: i32) -> i32 {
+        match self {
+            Direction::RightToLeft => x - num_cells * CHAR_WIDTH,
+            _ => x + num_cells * CHAR_WIDTH,
+        }
+    }
+
+    fn orient(self, glyph: [u8; 8]) -> [u8; 8] {
+        match self {
+            Direction::TopToBottom => rotate_90_counter_clockwise(glyph),
+            _ => glyph,
+        }
+    }
+}
+
+/// Draws a whole 8x8 glyph (both the lit and unlit leds) with its top left corner at x, y
+pub fn draw_glyph<C: Canvas + ?Sized>(canvas: &mut C, glyph: &[u8; 8], x: i32, y: i32) {
+    for (row, bits) in glyph.iter().enumerate() {
+        // glyph rows start at the bottom
+        let py = y + 7 - row as i32;
+        for col in 0..8 {
+            canvas.set_pixel(x + col, py, bits & (1 << col) != 0);
+        }
+    }
+}
+
+/// Draws a string one character per cell starting at x, y and returns the x position
+/// just after the last character (just before it for right to left text)
+pub fn draw_str<C: Canvas + ?Sized, F: Font + ?Sized>(
+    canvas: &mut C,
+    font: &F,
+    s: &str,
+    x: i32,
+    y: i32,
+    direction: Direction,
+) -> i32 {
+    let mut cells = 0;
+    for c in s.bytes() {
+        let glyph = direction.orient(font.glyph(c));
+        draw_glyph(canvas, &glyph, direction.cell_x(x, cells), y);
+        cells += 1;
+    }
+    direction.advance(x, cells)
+}
+
+/// Draws a message written in the inline markup language (see the markup module) and returns
+/// the x position just after the last character (just before it for right to left text).
+/// Blinking text is only drawn when blink_on is true
+pub fn draw_markup<C: Canvas + ?Sized, F: Font + ?Sized>(
+    canvas: &mut C,
+    font: &F,
+    markup: &str,
+    x: i32,
+    y: i32,
+    direction: Direction,
+    blink_on: bool,
+) -> i32 {
+    let mut cells = 0;
+    for item in Items::new(markup) {
+        if let Item::Char { c, style, blink } = item {
+            let glyph = if blink && !blink_on {
+                [0; 8]
+            } else {
+                direction.orient(style.apply(font.glyph(c)))
+            };
+            draw_glyph(canvas, &glyph, direction.cell_x(x, cells), y);
+            cells += 1;
+        }
+    }
+    direction.advance(x, cells)
+}
+
+/// Width in pixels of a message written in the inline markup language
+pub fn markup_width(markup: &str) -> i32 {
+    let cells = Items::new(markup)
+        .filter(|item| matches!(item, Item::Char { .. }))
+        .count();
+    cells as i32 * CHAR_WIDTH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FrameBuffer, CP437FONT};
+
+    fn glyph_at(c: u8, x: i32) -> FrameBuffer<4> {
+        let mut fb = FrameBuffer::<4>::new();
+        draw_glyph(&mut fb, &CP437FONT.glyph(c), x, 0);
+        fb
+    }
+
+    #[test]
+    fn right_to_left_fills_cells_leftwards_from_x() {
+        let mut fb = FrameBuffer::<4>::new();
+        let end = draw_str(&mut fb, &CP437FONT, "AB", 32, 0, Direction::RightToLeft);
+        assert_eq!(end, 16);
+        let a = glyph_at(b'A', 24);
+        let b = glyph_at(b'B', 16);
+        for y in 0..8 {
+            for x in 0..32 {
+                assert_eq!(
+                    fb.pixel(x, y),
+                    a.pixel(x, y) || b.pixel(x, y),
+                    "{} {}",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn top_to_bottom_reads_upright_with_device_0_at_the_top() {
+        let mut fb = FrameBuffer::<2>::new();
+        let end = draw_str(&mut fb, &CP437FONT, "LT", 0, 0, Direction::TopToBottom);
+        assert_eq!(end, 16);
+
+        // turning the chain a quarter turn clockwise puts device 0 at the top, the canvas x
+        // axis then runs down the chain and the canvas y axis runs right to left
+        for (cell, c) in [b'L', b'T'].iter().enumerate() {
+            let upright = glyph_at(*c, 0);
+            for row in 0..8 {
+                for col in 0..8 {
+                    let x = cell as i32 * CHAR_WIDTH + row;
+                    assert_eq!(
+                        fb.pixel(x, 7 - col),
+                        upright.pixel(col, row),
+                        "{} {}",
+                        col,
+                        row
+                    );
+                }
+            }
+        }
+
+        // the foot of the L is one row up from the bottom of the first device
+        assert!((1..8).all(|y| fb.pixel(6, y)));
+        assert!(!fb.pixel(6, 0));
+    }
+}
@@ -0,0 +1,314 @@
+use crate::canvas::Canvas;
+use crate::easing::{fraction, Easing, ONE};
+use crate::effect::scatter;
+use crate::framebuffer::FrameBuffer;
+
+/// Size of the squares used by the checkerboard transition
+const CHECKER_SIZE: i32 = 4;
+
+/// The ways of changing from one screen to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionKind {
+    /// the incoming screen slides in from the right over the outgoing one
+    SlideLeft,
+    /// the incoming screen slides in from the left over the outgoing one
+    SlideRight,
+    /// the incoming screen slides up from the bottom over the outgoing one
+    SlideUp,
+    /// the incoming screen slides down from the top over the outgoing one
+    SlideDown,
+    /// the incoming screen pushes the outgoing one off to the left
+    PushLeft,
+    /// the incoming screen pushes the outgoing one off to the right
+    PushRight,
+    /// the incoming screen pushes the outgoing one off the top
+    PushUp,
+    /// the incoming screen pushes the outgoing one off the bottom
+    PushDown,
+    /// the incoming screen is revealed from left to right
+    Wipe,
+    /// every other square is wiped in, then the rest
+    Checkerboard,
+    /// the incoming screen appears pixel by pixel in a scattered order
+    RandomDissolve,
+}
+
+/// Changes from one screen to another over a number of steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    /// number of frames the transition takes, the last frame is the incoming screen
+    pub steps: u32,
+    /// how the transition speeds up and slows down, None for constant speed. Elastic stops at
+    /// the incoming screen rather than overshooting it
+    pub easing: Option<Easing>,
+}
+
+impl Transition {
+    pub fn new(kind: TransitionKind, steps: u32) -> Self {
+        Transition {
+            kind,
+            steps,
+            easing: None,
+        }
+    }
+
+    /// Draws the frame for a step between 0 (the outgoing screen) and steps (the incoming screen).
+    /// from, to and out should all be the same size
+    pub fn draw_step<A, B, C>(&self, step: u32, from: &A, to: &B, out: &mut C)
+    where
+        A: Canvas + ?Sized,
+        B: Canvas + ?Sized,
+        C: Canvas + ?Sized,
+    {
+        let t = fraction(step, self.steps);
+        let t = match self.easing {
+            // elastic goes past ONE which would read beyond the edges of from and to
+            Some(easing) => easing.apply(t).clamp(0, ONE),
+            None => t,
+        };
+        let (width, height) = (out.width(), out.height());
+        let across = scale(width, t);
+        let down = scale(height, t);
+
+        for y in 0..height {
+            for x in 0..width {
+                let on = match self.kind {
+                    TransitionKind::SlideLeft => {
+                        slide(x, width - across, |x| (from.pixel(x, y), to.pixel(x, y)))
+                    }
+                    TransitionKind::SlideRight => slide(width - 1 - x, width - across, |x| {
+                        (from.pixel(width - 1 - x, y), to.pixel(width - 1 - x, y))
+                    }),
+                    TransitionKind::SlideUp => {
+                        slide(y, height - down, |y| (from.pixel(x, y), to.pixel(x, y)))
+                    }
+                    TransitionKind::SlideDown => slide(height - 1 - y, height - down, |y| {
+                        (from.pixel(x, height - 1 - y), to.pixel(x, height - 1 - y))
+                    }),
+                    TransitionKind::PushLeft => {
+                        if x < width - across {
+                            from.pixel(x + across, y)
+                        } else {
+                            to.pixel(x - width + across, y)
+                        }
+                    }
+                    TransitionKind::PushRight => {
+                        if x >= across {
+                            from.pixel(x - across, y)
+                        } else {
+                            to.pixel(x + width - across, y)
+                        }
+                    }
+                    TransitionKind::PushUp => {
+                        if y < height - down {
+                            from.pixel(x, y + down)
+                        } else {
+                            to.pixel(x, y - height + down)
+                        }
+                    }
+                    TransitionKind::PushDown => {
+                        if y >= down {
+                            from.pixel(x, y - down)
+                        } else {
+                            to.pixel(x, y + height - down)
+                        }
+                    }
+                    TransitionKind::Wipe => pick(x < across, from, to, x, y),
+                    TransitionKind::Checkerboard => {
+                        let parity = ((x / CHECKER_SIZE + y / CHECKER_SIZE) & 1) * ONE;
+                        let local = (2 * t - parity).clamp(0, ONE);
+                        pick(
+                            x % CHECKER_SIZE < scale(CHECKER_SIZE, local),
+                            from,
+                            to,
+                            x,
+                            y,
+                        )
+                    }
+                    TransitionKind::RandomDissolve => {
+                        pick((scatter(x, y) as i32) * ONE < t * 256, from, to, x, y)
+                    }
+                };
+                out.set_pixel(x, y, on);
+            }
+        }
+    }
+
+    /// Iterates over every frame of the transition between two framebuffers
+    pub fn frames<'a, const N: usize>(
+        &'a self,
+        from: &'a FrameBuffer<N>,
+        to: &'a FrameBuffer<N>,
+    ) -> Frames<'a, N> {
+        Frames {
+            transition: self,
+            from,
+            to,
+            step: 1,
+        }
+    }
+}
+
+/// The frames of a transition, see Transition::frames
+pub struct Frames<'a, const N: usize> {
+    transition: &'a Transition,
+    from: &'a FrameBuffer<N>,
+    to: &'a FrameBuffer<N>,
+    step: u32,
+}
+
+impl<'a, const N: usize> Iterator for Frames<'a, N> {
+    type Item = FrameBuffer<N>;
+
+    fn next(&mut self) -> Option<FrameBuffer<N>> {
+        if self.step > self.transition.steps.max(1) {
+            return None;
+        }
+        let mut frame = self.to.clone();
+        self.transition
+            .draw_step(self.step, self.from, self.to, &mut frame);
+        self.step += 1;
+        Some(frame)
+    }
+}
+
+fn scale(length: i32, t: i32) -> i32 {
+    length * t / ONE
+}
+
+fn pick<A, B>(incoming: bool, from: &A, to: &B, x: i32, y: i32) -> bool
+where
+    A: Canvas + ?Sized,
+    B: Canvas + ?Sized,
+{
+    if incoming {
+        to.pixel(x, y)
+    } else {
+        from.pixel(x, y)
+    }
+}
+
+/// The incoming screen starts at edge and covers everything after it, pixels gives the
+/// (outgoing, incoming) pixels at a position along the direction of travel
+fn slide(position: i32, edge: i32, pixels: impl Fn(i32) -> (bool, bool)) -> bool {
+    if position >= edge {
+        pixels(position - edge).1
+    } else {
+        pixels(position).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [TransitionKind; 11] = [
+        TransitionKind::SlideLeft,
+        TransitionKind::SlideRight,
+        TransitionKind::SlideUp,
+        TransitionKind::SlideDown,
+        TransitionKind::PushLeft,
+        TransitionKind::PushRight,
+        TransitionKind::PushUp,
+        TransitionKind::PushDown,
+        TransitionKind::Wipe,
+        TransitionKind::Checkerboard,
+        TransitionKind::RandomDissolve,
+    ];
+
+    /// A framebuffer that fails the test if a pixel outside of it is read
+    struct Strict(FrameBuffer<4>);
+
+    impl Canvas for Strict {
+        fn width(&self) -> i32 {
+            self.0.width()
+        }
+
+        fn height(&self) -> i32 {
+            self.0.height()
+        }
+
+        fn pixel(&self, x: i32, y: i32) -> bool {
+            assert!(
+                (0..32).contains(&x) && (0..8).contains(&y),
+                "read {} {}",
+                x,
+                y
+            );
+            self.0.pixel(x, y)
+        }
+
+        fn set_pixel(&mut self, x: i32, y: i32, on: bool) {
+            self.0.set_pixel(x, y, on);
+        }
+    }
+
+    fn from() -> FrameBuffer<4> {
+        pattern(|x, y| (x * 3 + y) % 5 == 0)
+    }
+
+    fn to() -> FrameBuffer<4> {
+        pattern(|x, y| (x + y * 7) % 3 == 0)
+    }
+
+    fn pattern(lit: fn(i32, i32) -> bool) -> FrameBuffer<4> {
+        let mut fb = FrameBuffer::<4>::new();
+        for y in 0..8 {
+            for x in 0..32 {
+                fb.set_pixel(x, y, lit(x, y));
+            }
+        }
+        fb
+    }
+
+    fn same(a: &FrameBuffer<4>, b: &FrameBuffer<4>) -> bool {
+        (0..8).all(|y| (0..32).all(|x| a.pixel(x, y) == b.pixel(x, y)))
+    }
+
+    #[test]
+    fn first_step_is_from_and_last_step_is_to() {
+        let (from, to) = (from(), to());
+        for kind in KINDS {
+            let transition = Transition::new(kind, 10);
+            let mut out = FrameBuffer::<4>::new();
+            transition.draw_step(0, &from, &to, &mut out);
+            assert!(same(&out, &from), "{:?}", kind);
+            transition.draw_step(10, &from, &to, &mut out);
+            assert!(same(&out, &to), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn frames_yields_steps_frames_ending_on_to() {
+        let (from, to) = (from(), to());
+        for kind in KINDS {
+            let transition = Transition::new(kind, 6);
+            assert_eq!(transition.frames(&from, &to).count(), 6, "{:?}", kind);
+            let last = transition.frames(&from, &to).last().unwrap();
+            assert!(same(&last, &to), "{:?}", kind);
+
+            // no steps jumps straight to the incoming screen
+            let instant = Transition::new(kind, 0);
+            let mut frames = instant.frames(&from, &to);
+            assert!(same(&frames.next().unwrap(), &to), "{:?}", kind);
+            assert!(frames.next().is_none());
+        }
+    }
+
+    #[test]
+    fn elastic_stays_inside_the_screens() {
+        let (from, to) = (Strict(from()), Strict(to()));
+        for kind in KINDS {
+            let transition = Transition {
+                easing: Some(Easing::Elastic),
+                ..Transition::new(kind, 40)
+            };
+            let mut out = FrameBuffer::<4>::new();
+            for step in 0..=40 {
+                transition.draw_step(step, &from, &to, &mut out);
+            }
+            assert!(same(&out, &to.0), "{:?}", kind);
+        }
+    }
+}
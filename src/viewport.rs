@@ -0,0 +1,131 @@
+use crate::canvas::Canvas;
+use crate::framebuffer::FrameBuffer;
+
+/// A window onto a canvas that is bigger than the display, for example a 512 pixel wide banner
+/// rendered once into a FrameBuffer<64>. Scrolling just moves the viewport, nothing is redrawn.
+/// With wrap on the canvas repeats forever in every direction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Viewport {
+    /// position on the source canvas of the top left corner of the display
+    pub x: i32,
+    pub y: i32,
+    pub wrap: bool,
+}
+
+impl Viewport {
+    pub fn new(x: i32, y: i32, wrap: bool) -> Self {
+        Viewport { x, y, wrap }
+    }
+
+    /// Moves the viewport. When wrapping the position is kept within the source canvas
+    pub fn scroll_by<S: Canvas + ?Sized>(&mut self, dx: i32, dy: i32, source: &S) {
+        self.x += dx;
+        self.y += dy;
+        if self.wrap {
+            self.x = self.x.rem_euclid(source.width().max(1));
+            self.y = self.y.rem_euclid(source.height().max(1));
+        }
+    }
+
+    /// Copies the visible part of the source canvas onto out
+    pub fn render<S: Canvas + ?Sized, C: Canvas + ?Sized>(&self, source: &S, out: &mut C) {
+        for y in 0..out.height() {
+            for x in 0..out.width() {
+                let on = self.source_pixel(source, self.x + x, self.y + y);
+                out.set_pixel(x, y, on);
+            }
+        }
+    }
+
+    /// Same as render but for framebuffers with all their devices in a single row it copies
+    /// whole bytes at a time, which is much quicker for long banners
+    pub fn render_framebuffer<const M: usize, const N: usize>(
+        &self,
+        source: &FrameBuffer<M>,
+        out: &mut FrameBuffer<N>,
+    ) {
+        if self.y != 0 || source.columns() != M || out.columns() != N || M == 0 {
+            self.render(source, out);
+            return;
+        }
+
+        let source_width = M as i32 * 8;
+        let shift = self.x.rem_euclid(8) as u32;
+        for device in 0..N {
+            let left = self.source_device(self.x.div_euclid(8) + device as i32, source_width);
+            let right = self.source_device(self.x.div_euclid(8) + device as i32 + 1, source_width);
+            let rows = out.device_mut(device);
+            for (line, row) in rows.iter_mut().enumerate() {
+                let left = left.map_or(0, |d| source.device(d)[line]) as u16;
+                let right = right.map_or(0, |d| source.device(d)[line]) as u16;
+                // bit 0 is the leftmost led so shifting right moves the image left
+                *row = ((left | right << 8) >> shift) as u8;
+            }
+        }
+    }
+
+    fn source_device(&self, device: i32, source_width: i32) -> Option<usize> {
+        let devices = source_width / 8;
+        if self.wrap {
+            Some(device.rem_euclid(devices) as usize)
+        } else if device >= 0 && device < devices {
+            Some(device as usize)
+        } else {
+            None
+        }
+    }
+
+    fn source_pixel<S: Canvas + ?Sized>(&self, source: &S, x: i32, y: i32) -> bool {
+        if self.wrap {
+            let (width, height) = (source.width(), source.height());
+            if width <= 0 || height <= 0 {
+                return false;
+            }
+            source.pixel(x.rem_euclid(width), y.rem_euclid(height))
+        } else {
+            source.pixel(x, y)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::Canvas;
+
+    fn pattern() -> FrameBuffer<6> {
+        let mut source = FrameBuffer::<6>::new();
+        for y in 0..8 {
+            for x in 0..48 {
+                source.set_pixel(x, y, (x * 7 + y * 3) % 5 < 2 || x == 47 - y);
+            }
+        }
+        source
+    }
+
+    #[test]
+    fn fast_path_matches_render() {
+        let source = pattern();
+        for wrap in [false, true] {
+            for x in -60..60 {
+                for y in -9..9 {
+                    let viewport = Viewport { x, y, wrap };
+                    let mut slow = FrameBuffer::<2>::new();
+                    viewport.render(&source, &mut slow);
+                    let mut fast = FrameBuffer::<2>::new();
+                    viewport.render_framebuffer(&source, &mut fast);
+                    for line in 0..8 {
+                        assert_eq!(
+                            fast.line(line),
+                            slow.line(line),
+                            "x {} y {} wrap {}",
+                            x,
+                            y,
+                            wrap
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
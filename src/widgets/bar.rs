@@ -0,0 +1,216 @@
+use crate::canvas::{Canvas, Point, Rect};
+use crate::draw::{fill_rect, line, Pen};
+use crate::easing::{sin_turns, ONE};
+
+/// The values at the empty and full ends of a widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl Range {
+    pub const fn new(min: i32, max: i32) -> Self {
+        Range { min, max }
+    }
+
+    /// How far along length pixels a value is, clamped to the range
+    pub fn scale(&self, value: i32, length: i32) -> i32 {
+        if self.max == self.min {
+            return 0;
+        }
+        let value = value.clamp(self.min.min(self.max), self.min.max(self.max));
+        let span = self.max as i64 - self.min as i64;
+        ((value as i64 - self.min as i64) * length as i64 / span) as i32
+    }
+}
+
+/// Values marked on top of an indicator bar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Marks {
+    /// lowest value seen, drawn as a dot on the bottom row
+    pub low: Option<i32>,
+    /// highest value seen, drawn as a dot on the top row
+    pub high: Option<i32>,
+    /// drawn as a line across the bar that shows up over the filled part too
+    pub threshold: Option<i32>,
+}
+
+/// A bar filling rect from the left
+pub fn horizontal_bar<C: Canvas + ?Sized>(canvas: &mut C, rect: Rect, range: Range, value: i32) {
+    let filled = range.scale(value, rect.width);
+    fill_rect(canvas, rect, Pen::Clear);
+    fill_rect(
+        canvas,
+        Rect::new(rect.x, rect.y, filled, rect.height),
+        Pen::Set,
+    );
+}
+
+/// A bar filling rect from the bottom
+pub fn vertical_bar<C: Canvas + ?Sized>(canvas: &mut C, rect: Rect, range: Range, value: i32) {
+    let filled = range.scale(value, rect.height);
+    fill_rect(canvas, rect, Pen::Clear);
+    fill_rect(
+        canvas,
+        Rect::new(rect.x, rect.bottom() - filled, rect.width, filled),
+        Pen::Set,
+    );
+}
+
+/// A horizontal progress bar split into segments with a one pixel gap between them.
+/// A segment is lit once the value reaches its right hand end
+pub fn segmented_bar<C: Canvas + ?Sized>(
+    canvas: &mut C,
+    rect: Rect,
+    range: Range,
+    value: i32,
+    segments: i32,
+) {
+    fill_rect(canvas, rect, Pen::Clear);
+    if segments <= 0 {
+        return;
+    }
+    let lit = range.scale(value, segments);
+    for segment in 0..lit {
+        let x = rect.x + segment * (rect.width + 1) / segments;
+        let end = rect.x + (segment + 1) * (rect.width + 1) / segments - 1;
+        fill_rect(canvas, Rect::new(x, rect.y, end - x, rect.height), Pen::Set);
+    }
+}
+
+/// A horizontal bar with the lowest and highest values seen and a threshold marked on it
+pub fn indicator<C: Canvas + ?Sized>(
+    canvas: &mut C,
+    rect: Rect,
+    range: Range,
+    value: i32,
+    marks: &Marks,
+) {
+    horizontal_bar(canvas, rect, range, value);
+    let x_of = |value: i32| rect.x + range.scale(value, rect.width - 1);
+    if let Some(low) = marks.low {
+        Pen::Toggle.plot(canvas, x_of(low), rect.bottom() - 1);
+    }
+    if let Some(high) = marks.high {
+        Pen::Toggle.plot(canvas, x_of(high), rect.y);
+    }
+    if let Some(threshold) = marks.threshold {
+        let x = x_of(threshold);
+        line(
+            canvas,
+            Point::new(x, rect.y),
+            Point::new(x, rect.bottom() - 1),
+            Pen::Toggle,
+        );
+    }
+}
+
+/// A half circle dial with a needle pivoting at the middle of the bottom edge, pointing left
+/// for the minimum and right for the maximum. An 8 row strip fits a dial 15 pixels wide
+pub fn gauge<C: Canvas + ?Sized>(canvas: &mut C, rect: Rect, range: Range, value: i32) {
+    fill_rect(canvas, rect, Pen::Clear);
+    let pivot = Point::new(rect.x + (rect.width - 1) / 2, rect.bottom() - 1);
+    let radius = (rect.height - 1).min((rect.width - 1) / 2);
+    if radius <= 0 {
+        return;
+    }
+
+    // the dial, every other pixel so the needle stands out
+    let dots = radius * 2;
+    for dot in (0..=dots).step_by(2) {
+        let end = point_on_dial(pivot, radius, fraction(dot, dots));
+        canvas.set_pixel(end.x, end.y, true);
+    }
+
+    let needle = point_on_dial(pivot, radius - 1, range.scale(value, ONE));
+    line(canvas, pivot, needle, Pen::Set);
+}
+
+fn fraction(part: i32, whole: i32) -> i32 {
+    part * ONE / whole
+}
+
+/// A point on the dial where t goes from 0 (pointing left) to ONE (pointing right)
+fn point_on_dial(pivot: Point, radius: i32, t: i32) -> Point {
+    // half a turn at the left round to no turn at the right
+    let angle = ONE / 2 - t / 2;
+    let cos = sin_turns(angle + ONE / 4);
+    let sin = sin_turns(angle);
+    Point::new(
+        pivot.x + (radius * cos + ONE / 2).div_euclid(ONE),
+        pivot.y - (radius * sin + ONE / 2).div_euclid(ONE),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FrameBuffer;
+
+    #[test]
+    fn scale_clamps_to_the_range() {
+        let range = Range::new(0, 100);
+        assert_eq!(range.scale(50, 8), 4);
+        assert_eq!(range.scale(-10, 8), 0);
+        assert_eq!(range.scale(500, 8), 8);
+        assert_eq!(Range::new(5, 5).scale(5, 8), 0);
+        // a reversed range fills as the value goes down
+        assert_eq!(Range::new(100, 0).scale(25, 8), 6);
+    }
+
+    #[test]
+    fn scale_handles_the_whole_i32_range() {
+        let range = Range::new(-2_000_000_000, 2_000_000_000);
+        assert_eq!(range.scale(1_000_000_000, 8), 6);
+        let range = Range::new(i32::MIN, i32::MAX);
+        assert_eq!(range.scale(i32::MAX, 8), 8);
+        assert_eq!(range.scale(i32::MIN, 8), 0);
+    }
+
+    #[test]
+    fn segments_light_at_their_right_hand_end() {
+        let lit_columns = |value| {
+            let mut fb = FrameBuffer::<2>::new();
+            segmented_bar(
+                &mut fb,
+                Rect::new(0, 0, 15, 8),
+                Range::new(0, 100),
+                value,
+                4,
+            );
+            let mut columns = [false; 16];
+            for (x, column) in columns.iter_mut().enumerate() {
+                *column = fb.pixel(x as i32, 0);
+            }
+            columns
+        };
+        assert_eq!(lit_columns(24), [false; 16]);
+
+        // 3 pixel segments with a one pixel gap between them
+        let one = lit_columns(25);
+        assert!(one[..3].iter().all(|lit| *lit));
+        assert!(!one[3]);
+        assert!(one[4..].iter().all(|lit| !*lit));
+
+        let all = lit_columns(100);
+        for (x, lit) in all.iter().enumerate().take(15) {
+            assert_eq!(*lit, x % 4 != 3, "x {}", x);
+        }
+        assert!(!all[15]);
+    }
+
+    #[test]
+    fn gauge_needle_points_left_up_and_right() {
+        let needle_end = |value| {
+            let mut fb = FrameBuffer::<2>::new();
+            gauge(&mut fb, Rect::new(0, 0, 15, 8), Range::new(0, 100), value);
+            fb
+        };
+        // the pivot is at 7, 7 with a needle 6 pixels long
+        assert!(needle_end(0).pixel(1, 7));
+        assert!(needle_end(50).pixel(7, 1));
+        assert!(needle_end(100).pixel(13, 7));
+        assert!(!needle_end(0).pixel(13, 7));
+    }
+}
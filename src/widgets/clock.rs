@@ -0,0 +1,276 @@
+use crate::bitmap::{blit, Bitmap, BlendMode};
+use crate::canvas::{Canvas, Rect, Window};
+use crate::draw::{fill_rect, Pen};
+use crate::easing::{fraction, Easing, ONE};
+
+/// Height of the clock digits, one row less than a module so they roll with a blank row between
+pub const DIGIT_HEIGHT: i32 = 7;
+
+const LARGE_WIDTH: i32 = 5;
+const SMALL_WIDTH: i32 = 3;
+
+/// 5x7 digits, four of them and a colon fit in 4 modules
+const LARGE_DIGITS: [[u8; 7]; 10] = [
+    [0x70, 0x88, 0x98, 0xA8, 0xC8, 0x88, 0x70], // 0
+    [0x20, 0x60, 0x20, 0x20, 0x20, 0x20, 0x70], // 1
+    [0x70, 0x88, 0x08, 0x10, 0x20, 0x40, 0xF8], // 2
+    [0xF8, 0x10, 0x20, 0x10, 0x08, 0x88, 0x70], // 3
+    [0x10, 0x30, 0x50, 0x90, 0xF8, 0x10, 0x10], // 4
+    [0xF8, 0x80, 0xF0, 0x08, 0x08, 0x88, 0x70], // 5
+    [0x30, 0x40, 0x80, 0xF0, 0x88, 0x88, 0x70], // 6
+    [0xF8, 0x08, 0x10, 0x20, 0x40, 0x40, 0x40], // 7
+    [0x70, 0x88, 0x88, 0x70, 0x88, 0x88, 0x70], // 8
+    [0x70, 0x88, 0x88, 0x78, 0x08, 0x10, 0x60], // 9
+];
+
+/// 3x7 digits, six of them and two colons fit in 4 modules
+const SMALL_DIGITS: [[u8; 7]; 10] = [
+    [0xE0, 0xA0, 0xA0, 0xA0, 0xA0, 0xA0, 0xE0], // 0
+    [0x40, 0xC0, 0x40, 0x40, 0x40, 0x40, 0xE0], // 1
+    [0xE0, 0x20, 0x20, 0xE0, 0x80, 0x80, 0xE0], // 2
+    [0xE0, 0x20, 0x20, 0xE0, 0x20, 0x20, 0xE0], // 3
+    [0xA0, 0xA0, 0xA0, 0xE0, 0x20, 0x20, 0x20], // 4
+    [0xE0, 0x80, 0x80, 0xE0, 0x20, 0x20, 0xE0], // 5
+    [0xE0, 0x80, 0x80, 0xE0, 0xA0, 0xA0, 0xE0], // 6
+    [0xE0, 0x20, 0x20, 0x40, 0x40, 0x40, 0x40], // 7
+    [0xE0, 0xA0, 0xA0, 0xE0, 0xA0, 0xA0, 0xE0], // 8
+    [0xE0, 0xA0, 0xA0, 0xE0, 0x20, 0x20, 0xE0], // 9
+];
+
+const AM: [u8; 7] = [0x40, 0xA0, 0xA0, 0xE0, 0xA0, 0xA0, 0xA0];
+const PM: [u8; 7] = [0xC0, 0xA0, 0xA0, 0xC0, 0x80, 0x80, 0x80];
+
+// stands in for a digit that is not shown, like the leading zero of 12 hour times
+const BLANK: u8 = 10;
+
+/// Which parts of the time are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockFormat {
+    /// 24 hour hh:mm with large digits
+    HoursMinutes,
+    /// 24 hour hh:mm:ss with small digits
+    HoursMinutesSeconds,
+    /// 12 hour h:mm with large digits followed by A or P
+    TwelveHour,
+}
+
+/// A digital clock. The caller passes in the time whenever it has it and the clock draws it
+/// with narrow digits so it fits on 4 modules, blinking the colons and rolling each digit up
+/// when it changes
+pub struct Clock {
+    format: ClockFormat,
+    digits: [u8; 6],
+    previous: [u8; 6],
+    changed_at: [u32; 6],
+    pm: bool,
+    /// how long a digit takes to roll up to the new one, 0 swaps digits straight away
+    pub roll_ms: u32,
+    pub roll_easing: Easing,
+    /// a full on and off cycle of the colons, 0 keeps them lit
+    pub blink_period_ms: u32,
+}
+
+impl Clock {
+    pub const fn new(format: ClockFormat) -> Self {
+        Clock {
+            format,
+            digits: [BLANK; 6],
+            previous: [BLANK; 6],
+            changed_at: [0; 6],
+            pm: false,
+            roll_ms: 300,
+            roll_easing: Easing::EaseOut,
+            blink_period_ms: 1000,
+        }
+    }
+
+    pub fn format(&self) -> ClockFormat {
+        self.format
+    }
+
+    /// Changes the format, the new digits show up on the next set_time
+    pub fn set_format(&mut self, format: ClockFormat) {
+        self.format = format;
+    }
+
+    /// Width in pixels of the clock as drawn
+    pub fn width(&self) -> i32 {
+        match self.format {
+            ClockFormat::HoursMinutes => 4 * (LARGE_WIDTH + 1) + 1,
+            ClockFormat::HoursMinutesSeconds => 6 * (SMALL_WIDTH + 1) + 3,
+            ClockFormat::TwelveHour => 4 * (LARGE_WIDTH + 1) + 2 + SMALL_WIDTH,
+        }
+    }
+
+    /// Updates the time, any digit that changed starts rolling up at now_ms.
+    /// Hours are 0 to 23 in every format
+    pub fn set_time(&mut self, hours: u8, minutes: u8, seconds: u8, now_ms: u32) {
+        let shown_hours = match self.format {
+            ClockFormat::TwelveHour => match hours % 12 {
+                0 => 12,
+                h => h,
+            },
+            _ => hours % 24,
+        };
+        let tens_of_hours = match self.format {
+            ClockFormat::TwelveHour if shown_hours < 10 => BLANK,
+            _ => shown_hours / 10,
+        };
+        let digits = [
+            tens_of_hours,
+            shown_hours % 10,
+            minutes / 10 % 6,
+            minutes % 10,
+            seconds / 10 % 6,
+            seconds % 10,
+        ];
+        for (slot, digit) in digits.iter().enumerate() {
+            if *digit != self.digits[slot] {
+                self.previous[slot] = self.digits[slot];
+                self.digits[slot] = *digit;
+                self.changed_at[slot] = now_ms;
+            }
+        }
+        self.pm = hours % 24 >= 12;
+    }
+
+    /// True while any digit is still rolling so the caller knows to keep redrawing
+    pub fn is_rolling(&self, now_ms: u32) -> bool {
+        (0..self.slots()).any(|slot| self.roll_offset(slot, now_ms).is_some())
+    }
+
+    /// True while the colons are lit
+    pub fn colon_on(&self, now_ms: u32) -> bool {
+        self.blink_period_ms == 0 || now_ms % self.blink_period_ms < self.blink_period_ms / 2
+    }
+
+    /// Clears rect and draws the clock centred in it
+    pub fn draw<C: Canvas + ?Sized>(&self, canvas: &mut C, rect: Rect, now_ms: u32) {
+        fill_rect(canvas, rect, Pen::Clear);
+        let (digit_width, table) = match self.format {
+            ClockFormat::HoursMinutesSeconds => (SMALL_WIDTH, &SMALL_DIGITS),
+            _ => (LARGE_WIDTH, &LARGE_DIGITS),
+        };
+        let mut x = rect.x + (rect.width - self.width()) / 2;
+        let y = rect.y + (rect.height - DIGIT_HEIGHT) / 2;
+        let colon = self.colon_on(now_ms);
+
+        for slot in 0..self.slots() {
+            if slot > 0 && slot % 2 == 0 {
+                if colon {
+                    canvas.set_pixel(x, y + 1, true);
+                    canvas.set_pixel(x, y + 5, true);
+                }
+                x += 2;
+            }
+            let mut cell = Window::new(canvas, x, y, digit_width, DIGIT_HEIGHT);
+            match self.roll_offset(slot, now_ms) {
+                Some(offset) => {
+                    draw_digit(&mut cell, table, digit_width, self.previous[slot], -offset);
+                    let incoming = DIGIT_HEIGHT + 1 - offset;
+                    draw_digit(&mut cell, table, digit_width, self.digits[slot], incoming);
+                }
+                None => draw_digit(&mut cell, table, digit_width, self.digits[slot], 0),
+            }
+            x += digit_width + 1;
+        }
+
+        if self.format == ClockFormat::TwelveHour {
+            let marker = if self.pm { &PM } else { &AM };
+            let bitmap = Bitmap::new(SMALL_WIDTH, DIGIT_HEIGHT, marker);
+            blit(canvas, &bitmap, x, y, BlendMode::Or, None);
+        }
+    }
+
+    fn slots(&self) -> usize {
+        match self.format {
+            ClockFormat::HoursMinutesSeconds => 6,
+            _ => 4,
+        }
+    }
+
+    // how far the old digit has rolled up, None once the roll is over
+    fn roll_offset(&self, slot: usize, now_ms: u32) -> Option<i32> {
+        let elapsed = now_ms.wrapping_sub(self.changed_at[slot]);
+        if elapsed >= self.roll_ms {
+            return None;
+        }
+        let progress = self.roll_easing.apply(fraction(elapsed, self.roll_ms));
+        Some(progress * (DIGIT_HEIGHT + 1) / ONE)
+    }
+}
+
+fn draw_digit<C: Canvas + ?Sized>(
+    canvas: &mut C,
+    table: &[[u8; 7]; 10],
+    width: i32,
+    digit: u8,
+    y: i32,
+) {
+    if let Some(rows) = table.get(digit as usize) {
+        let bitmap = Bitmap::new(width, DIGIT_HEIGHT, rows);
+        blit(canvas, &bitmap, 0, y, BlendMode::Or, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FrameBuffer;
+
+    const FORMATS: [ClockFormat; 3] = [
+        ClockFormat::HoursMinutes,
+        ClockFormat::HoursMinutesSeconds,
+        ClockFormat::TwelveHour,
+    ];
+
+    fn lit_columns(fb: &FrameBuffer<4>) -> (i32, i32) {
+        let lit = |x: i32| (0..8).any(|y| fb.pixel(x, y));
+        let first = (0..32).find(|&x| lit(x)).unwrap();
+        let last = (0..32).rev().find(|&x| lit(x)).unwrap();
+        (first, last)
+    }
+
+    #[test]
+    fn drawn_width_matches_width() {
+        for format in FORMATS {
+            let mut clock = Clock::new(format);
+            // 2s and 0s light both edges of their cells, so does the A of 12 hour times but the
+            // first digit of those is always a 1 or blank
+            clock.set_time(20, 20, 20, 0);
+            let mut fb = FrameBuffer::<4>::new();
+            clock.draw(&mut fb, Rect::new(0, 0, 32, 8), 5000);
+            let (first, last) = lit_columns(&fb);
+            let start = (32 - clock.width()) / 2;
+            assert_eq!(last, start + clock.width() - 1, "{:?}", format);
+            if format == ClockFormat::TwelveHour {
+                assert!(first >= start);
+            } else {
+                assert_eq!(first, start, "{:?}", format);
+            }
+            assert!(clock.width() <= 32);
+        }
+    }
+
+    #[test]
+    fn twelve_hour_drops_the_leading_zero() {
+        let mut clock = Clock::new(ClockFormat::TwelveHour);
+        clock.set_time(0, 5, 0, 0);
+        assert_eq!(&clock.digits[..4], &[1, 2, 0, 5]);
+        assert!(!clock.pm);
+        clock.set_time(13, 5, 0, 0);
+        assert_eq!(&clock.digits[..4], &[BLANK, 1, 0, 5]);
+        assert!(clock.pm);
+    }
+
+    #[test]
+    fn changed_digits_roll_then_settle() {
+        let mut clock = Clock::new(ClockFormat::HoursMinutes);
+        clock.set_time(9, 59, 0, 0);
+        assert!(!clock.is_rolling(1000));
+        clock.set_time(10, 0, 0, 1000);
+        assert!(clock.is_rolling(1100));
+        assert_eq!(clock.previous[..4], [0, 9, 5, 9]);
+        assert!(!clock.is_rolling(1000 + clock.roll_ms));
+    }
+}
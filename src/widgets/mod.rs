@@ -0,0 +1,7 @@
+//! Ready-made renderers for dashboards. They all draw onto a Canvas so they can be used with
+//! a FrameBuffer or any other surface
+pub mod bar;
+pub mod clock;
+pub mod number;
+pub mod sparkline;
+pub mod spectrum;
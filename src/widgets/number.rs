@@ -0,0 +1,286 @@
+use crate::canvas::Canvas;
+use crate::font::Font;
+use core::fmt::{self, Write};
+
+/// Longest string a value can be formatted to
+pub const MAX_LEN: usize = 20;
+
+/// Gap in pixels between characters
+const SPACING: i32 = 1;
+
+/// How a value is padded and spaced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// pad the text to at least this many characters
+    pub min_width: u8,
+    /// pad with zeros (after any sign) instead of spaces
+    pub leading_zeros: bool,
+    /// give every digit (and padding space) the same width so numbers don't jiggle when they
+    /// change. Otherwise every character is only as wide as its lit leds
+    pub tabular: bool,
+}
+
+/// Layout of a duration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationFormat {
+    /// mm:ss, minutes keep counting past 59
+    MinutesSeconds,
+    /// hh:mm, hours keep counting past 23
+    HoursMinutes,
+}
+
+/// A value to be formatted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    /// a whole number like 42
+    Int(i32),
+    /// a fixed-point number scaled by 10^decimals, Fixed { value: 215, decimals: 1 } is 21.5
+    Fixed { value: i32, decimals: u8 },
+    /// a whole number followed by a % sign
+    Percent(i32),
+    /// upper case hexadecimal without a prefix
+    Hex(u32),
+    /// a number of seconds
+    Duration(u32, DurationFormat),
+}
+
+/// A formatted value stored without using the heap
+#[derive(Clone)]
+pub struct NumberBuf {
+    buf: [u8; MAX_LEN],
+    len: usize,
+}
+
+impl NumberBuf {
+    const fn new() -> Self {
+        NumberBuf {
+            buf: [0; MAX_LEN],
+            len: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only ascii is ever written
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+}
+
+impl fmt::Write for NumberBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // keep as much as fits so long values are truncated rather than dropped
+        let count = s.len().min(MAX_LEN - self.len);
+        self.buf[self.len..self.len + count].copy_from_slice(&s.as_bytes()[..count]);
+        self.len += count;
+        if count < s.len() {
+            return Err(fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+/// Formats a value into a fixed size buffer. Values that do not fit in MAX_LEN characters
+/// are truncated
+pub fn format_value(value: Value, format: &NumberFormat) -> NumberBuf {
+    let mut unpadded = NumberBuf::new();
+    let (negative, magnitude) = match value {
+        Value::Int(v) | Value::Percent(v) | Value::Fixed { value: v, .. } => {
+            (v < 0, v.unsigned_abs())
+        }
+        _ => (false, 0),
+    };
+
+    let _ = match value {
+        Value::Int(_) => write!(unpadded, "{}", magnitude),
+        Value::Percent(_) => write!(unpadded, "{}%", magnitude),
+        Value::Fixed { decimals: 0, .. } => write!(unpadded, "{}", magnitude),
+        Value::Fixed { decimals, .. } => {
+            let scale = 10u32.saturating_pow(decimals as u32);
+            write!(
+                unpadded,
+                "{}.{:0width$}",
+                magnitude / scale,
+                magnitude % scale,
+                width = decimals as usize
+            )
+        }
+        Value::Hex(v) => write!(unpadded, "{:X}", v),
+        Value::Duration(seconds, DurationFormat::MinutesSeconds) => {
+            write!(unpadded, "{:02}:{:02}", seconds / 60, seconds % 60)
+        }
+        Value::Duration(seconds, DurationFormat::HoursMinutes) => {
+            write!(unpadded, "{:02}:{:02}", seconds / 3600, (seconds / 60) % 60)
+        }
+    };
+
+    let len = unpadded.len + negative as usize;
+    // never pad so much that the value itself no longer fits
+    let padding = (format.min_width as usize)
+        .saturating_sub(len)
+        .min(MAX_LEN.saturating_sub(len));
+    let mut formatted = NumberBuf::new();
+    if !format.leading_zeros {
+        for _ in 0..padding {
+            let _ = formatted.write_char(' ');
+        }
+    }
+    if negative {
+        let _ = formatted.write_char('-');
+    }
+    if format.leading_zeros {
+        for _ in 0..padding {
+            let _ = formatted.write_char('0');
+        }
+    }
+    let _ = formatted.write_str(unpadded.as_str());
+    formatted
+}
+
+/// Formats and draws a value with its top left corner at x, y and returns the x position just
+/// after the last character
+pub fn draw_value<C: Canvas + ?Sized, F: Font + ?Sized>(
+    canvas: &mut C,
+    font: &F,
+    value: Value,
+    format: &NumberFormat,
+    x: i32,
+    y: i32,
+) -> i32 {
+    let text = format_value(value, format);
+    draw_spaced(canvas, font, text.as_str(), format.tabular, x, y)
+}
+
+/// Draws text using only the lit columns of each glyph plus a one pixel gap.
+/// When tabular is true digits and spaces are centered in cells as wide as the widest digit
+pub fn draw_spaced<C: Canvas + ?Sized, F: Font + ?Sized>(
+    canvas: &mut C,
+    font: &F,
+    s: &str,
+    tabular: bool,
+    x: i32,
+    y: i32,
+) -> i32 {
+    let digit_width = (b'0'..=b'9')
+        .filter_map(|c| ink(&font.glyph(c)))
+        .map(|(first, end)| end - first)
+        .max()
+        .unwrap_or(8);
+
+    let mut x = x;
+    for c in s.bytes() {
+        let glyph = font.glyph(c);
+        let (first, end) = ink(&glyph).unwrap_or((0, digit_width / 2));
+        let width = end - first;
+        if tabular && (c.is_ascii_digit() || c == b' ') {
+            let offset = (digit_width - width) / 2;
+            clear_columns(canvas, x, y, digit_width + SPACING);
+            draw_columns(canvas, &glyph, x + offset, y, first, end);
+            x += digit_width + SPACING;
+        } else {
+            clear_columns(canvas, x, y, width + SPACING);
+            draw_columns(canvas, &glyph, x, y, first, end);
+            x += width + SPACING;
+        }
+    }
+    x
+}
+
+/// The first lit column and the column after the last lit column, None for a blank glyph
+fn ink(glyph: &[u8; 8]) -> Option<(i32, i32)> {
+    let columns = glyph.iter().fold(0, |acc, row| acc | row);
+    if columns == 0 {
+        None
+    } else {
+        Some((
+            columns.trailing_zeros() as i32,
+            8 - columns.leading_zeros() as i32,
+        ))
+    }
+}
+
+fn draw_columns<C: Canvas + ?Sized>(
+    canvas: &mut C,
+    glyph: &[u8; 8],
+    x: i32,
+    y: i32,
+    first: i32,
+    end: i32,
+) {
+    for (row, bits) in glyph.iter().enumerate() {
+        for col in first..end {
+            if bits & (1 << col) != 0 {
+                canvas.set_pixel(x + col - first, y + 7 - row as i32, true);
+            }
+        }
+    }
+}
+
+fn clear_columns<C: Canvas + ?Sized>(canvas: &mut C, x: i32, y: i32, width: i32) {
+    for py in y..y + 8 {
+        for px in x..x + width {
+            canvas.set_pixel(px, py, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(value: Value) -> NumberBuf {
+        format_value(value, &NumberFormat::default())
+    }
+
+    #[test]
+    fn formats_each_kind_of_value() {
+        assert_eq!(plain(Value::Int(-42)).as_str(), "-42");
+        assert_eq!(plain(Value::Int(i32::MIN)).as_str(), "-2147483648");
+        assert_eq!(plain(Value::Percent(75)).as_str(), "75%");
+        assert_eq!(plain(Value::Hex(0xBEEF)).as_str(), "BEEF");
+        let mm_ss = Value::Duration(125, DurationFormat::MinutesSeconds);
+        assert_eq!(plain(mm_ss).as_str(), "02:05");
+        let hh_mm = Value::Duration(3700, DurationFormat::HoursMinutes);
+        assert_eq!(plain(hh_mm).as_str(), "01:01");
+    }
+
+    #[test]
+    fn fixed_point_keeps_the_sign_below_one() {
+        let fixed = |value, decimals| plain(Value::Fixed { value, decimals });
+        assert_eq!(fixed(215, 1).as_str(), "21.5");
+        assert_eq!(fixed(7, 2).as_str(), "0.07");
+        assert_eq!(fixed(-5, 1).as_str(), "-0.5");
+        assert_eq!(fixed(-5, 0).as_str(), "-5");
+    }
+
+    #[test]
+    fn pads_after_the_sign_with_leading_zeros() {
+        let spaces = NumberFormat {
+            min_width: 5,
+            ..NumberFormat::default()
+        };
+        let zeros = NumberFormat {
+            leading_zeros: true,
+            ..spaces
+        };
+        assert_eq!(format_value(Value::Int(-42), &spaces).as_str(), "  -42");
+        assert_eq!(format_value(Value::Int(-42), &zeros).as_str(), "-0042");
+        assert_eq!(format_value(Value::Int(123456), &zeros).as_str(), "123456");
+    }
+
+    #[test]
+    fn truncates_at_max_len() {
+        let wide = NumberFormat {
+            min_width: 30,
+            ..NumberFormat::default()
+        };
+        let padded = format_value(Value::Int(-7), &wide);
+        assert_eq!(padded.as_str().len(), MAX_LEN);
+        assert!(padded.as_str().ends_with("-7"));
+
+        let long = plain(Value::Fixed {
+            value: 1,
+            decimals: 25,
+        });
+        assert_eq!(long.as_str().len(), MAX_LEN);
+        assert!(long.as_str().starts_with("0.000"));
+    }
+}
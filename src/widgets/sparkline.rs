@@ -0,0 +1,187 @@
+use crate::canvas::{Canvas, Point, Rect};
+use crate::draw::{fill_rect, line, Pen};
+use crate::widgets::bar::Range;
+
+/// How sample values are mapped onto the height of the chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    /// from the lowest to the highest sample currently on the chart
+    Auto,
+    /// a fixed range, samples outside of it are clamped
+    Fixed(Range),
+}
+
+/// How the samples are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartStyle {
+    /// a line joining the samples
+    Line,
+    /// a column rising from the bottom for every sample
+    Columns,
+}
+
+/// Keeps the last N samples in a ring buffer and draws them one column per sample with the
+/// newest on the right, so pushing a sample shifts the chart left by one column
+pub struct Sparkline<const N: usize> {
+    samples: [i32; N],
+    len: usize,
+    next: usize,
+    pub scale: Scale,
+    pub style: ChartStyle,
+}
+
+impl<const N: usize> Sparkline<N> {
+    pub const fn new(scale: Scale, style: ChartStyle) -> Self {
+        Sparkline {
+            samples: [0; N],
+            len: 0,
+            next: 0,
+            scale,
+            style,
+        }
+    }
+
+    /// Adds a sample, dropping the oldest once N samples are held
+    pub fn push(&mut self, value: i32) {
+        if N == 0 {
+            return;
+        }
+        self.samples[self.next] = value;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.next = 0;
+    }
+
+    /// The newest sample first, going back in time
+    pub fn newest(&self) -> impl Iterator<Item = i32> + '_ {
+        (1..=self.len).map(move |age| self.samples[(self.next + N - age) % N])
+    }
+
+    /// Draws as many of the newest samples as fit across rect
+    pub fn draw<C: Canvas + ?Sized>(&self, canvas: &mut C, rect: Rect) {
+        fill_rect(canvas, rect, Pen::Clear);
+        if rect.is_empty() || self.is_empty() {
+            return;
+        }
+
+        let visible = self.len.min(rect.width as usize);
+        let range = match self.scale {
+            Scale::Fixed(range) => range,
+            Scale::Auto => {
+                let min = self.newest().take(visible).min().unwrap_or(0);
+                let max = self.newest().take(visible).max().unwrap_or(0);
+                // a flat line sits at the bottom rather than dividing by zero
+                Range::new(min, max.max(min.saturating_add(1)))
+            }
+        };
+
+        let mut previous: Option<Point> = None;
+        for (age, value) in self.newest().take(visible).enumerate() {
+            let x = rect.right() - 1 - age as i32;
+            let height = range.scale(value, rect.height - 1);
+            let point = Point::new(x, rect.bottom() - 1 - height);
+            match self.style {
+                ChartStyle::Columns => {
+                    fill_rect(canvas, Rect::new(x, point.y, 1, height + 1), Pen::Set);
+                }
+                ChartStyle::Line => match previous {
+                    Some(previous) => line(canvas, point, previous, Pen::Set),
+                    None => canvas.set_pixel(point.x, point.y, true),
+                },
+            }
+            previous = Some(point);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FrameBuffer;
+
+    fn column(fb: &FrameBuffer<4>, x: i32) -> [bool; 8] {
+        let mut column = [false; 8];
+        for (y, pixel) in column.iter_mut().enumerate() {
+            *pixel = fb.pixel(x, y as i32);
+        }
+        column
+    }
+
+    #[test]
+    fn ring_buffer_keeps_the_newest_samples() {
+        let mut sparkline = Sparkline::<3>::new(Scale::Auto, ChartStyle::Line);
+        for value in 1..=5 {
+            sparkline.push(value);
+        }
+        assert_eq!(sparkline.len(), 3);
+        let mut newest = sparkline.newest();
+        assert_eq!(newest.next(), Some(5));
+        assert_eq!(newest.next(), Some(4));
+        assert_eq!(newest.next(), Some(3));
+        assert_eq!(newest.next(), None);
+        drop(newest);
+
+        sparkline.clear();
+        assert!(sparkline.is_empty());
+        assert_eq!(sparkline.newest().next(), None);
+    }
+
+    #[test]
+    fn newest_sample_is_in_the_rightmost_column() {
+        let scale = Scale::Fixed(Range::new(0, 100));
+        let mut sparkline = Sparkline::<40>::new(scale, ChartStyle::Columns);
+        sparkline.push(0);
+        sparkline.push(100);
+        let mut fb = FrameBuffer::<4>::new();
+        sparkline.draw(&mut fb, Rect::new(0, 0, 32, 8));
+        assert_eq!(column(&fb, 31), [true; 8]);
+        assert_eq!(
+            column(&fb, 30),
+            [false, false, false, false, false, false, false, true]
+        );
+        assert_eq!(column(&fb, 29), [false; 8]);
+    }
+
+    #[test]
+    fn auto_scale_fits_the_visible_samples() {
+        let mut auto = Sparkline::<8>::new(Scale::Auto, ChartStyle::Line);
+        let fixed = Scale::Fixed(Range::new(0, 100));
+        let mut fixed = Sparkline::<8>::new(fixed, ChartStyle::Line);
+        for value in [10, 20] {
+            auto.push(value);
+            fixed.push(value);
+        }
+
+        let mut fb = FrameBuffer::<4>::new();
+        auto.draw(&mut fb, Rect::new(0, 0, 32, 8));
+        assert!(fb.pixel(31, 0));
+        assert!(fb.pixel(30, 7));
+
+        fixed.draw(&mut fb, Rect::new(0, 0, 32, 8));
+        assert!(fb.pixel(31, 6));
+        assert!(fb.pixel(30, 7));
+        assert!(!fb.pixel(31, 0));
+    }
+
+    #[test]
+    fn flat_line_at_the_extremes_does_not_overflow() {
+        let mut sparkline = Sparkline::<4>::new(Scale::Auto, ChartStyle::Columns);
+        sparkline.push(i32::MAX);
+        let mut fb = FrameBuffer::<4>::new();
+        sparkline.draw(&mut fb, Rect::new(0, 0, 32, 8));
+        assert!(fb.pixel(31, 7));
+        assert!(!fb.pixel(31, 6));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::canvas::{Canvas, Rect};
+use crate::draw::{fill_rect, Pen};
+use crate::easing::ONE;
+use crate::widgets::bar::Range;
+
+/// How the bands are laid out across the display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpectrumStyle {
+    /// columns rise from the bottom, lowest band on the left
+    Bottom,
+    /// columns grow up and down from the middle row
+    Centered,
+    /// lowest band in the middle with the rest mirrored out to both edges
+    Mirrored,
+}
+
+/// Turns band magnitudes supplied by the caller (from an FFT or a filter bank for example) into
+/// columns across the display. Columns jump up straight away but fall back slowly and a peak
+/// dot is held above each column for a while before it falls too.
+/// Two bands drawn with draw_vu make a stereo VU meter
+pub struct Spectrum<const B: usize> {
+    // levels and peaks are fractions of the full height where ONE is full
+    levels: [i32; B],
+    peaks: [i32; B],
+    peak_hold: [u32; B],
+    pub range: Range,
+    pub style: SpectrumStyle,
+    /// how far a column may fall per update, ONE being the full height
+    pub decay: i32,
+    /// how many updates a peak stays put before falling
+    pub peak_hold_updates: u32,
+    /// how far a peak falls per update once its hold is over, ONE being the full height
+    pub peak_decay: i32,
+    /// whether to draw the peak dots
+    pub show_peaks: bool,
+}
+
+impl<const B: usize> Spectrum<B> {
+    pub const fn new(range: Range, style: SpectrumStyle) -> Self {
+        Spectrum {
+            levels: [0; B],
+            peaks: [0; B],
+            peak_hold: [0; B],
+            range,
+            style,
+            decay: ONE / 8,
+            peak_hold_updates: 10,
+            peak_decay: ONE / 16,
+            show_peaks: true,
+        }
+    }
+
+    /// Feeds in the latest magnitude for each band, extra magnitudes are ignored
+    pub fn update(&mut self, magnitudes: &[i32]) {
+        for (band, magnitude) in magnitudes.iter().enumerate().take(B) {
+            let target = self.range.scale(*magnitude, ONE);
+            self.levels[band] = target.max(self.levels[band] - self.decay);
+
+            if self.levels[band] >= self.peaks[band] {
+                self.peaks[band] = self.levels[band];
+                self.peak_hold[band] = self.peak_hold_updates;
+            } else if self.peak_hold[band] > 0 {
+                self.peak_hold[band] -= 1;
+            } else {
+                self.peaks[band] = (self.peaks[band] - self.peak_decay).max(self.levels[band]);
+            }
+        }
+    }
+
+    /// Draws the bands as columns spread evenly across rect with a one pixel gap between them
+    /// when there is room. On 8x8 modules a width of 8 pixels per band fits one band per device
+    pub fn draw<C: Canvas + ?Sized>(&self, canvas: &mut C, rect: Rect) {
+        fill_rect(canvas, rect, Pen::Clear);
+        if B == 0 || rect.is_empty() {
+            return;
+        }
+
+        let slots = match self.style {
+            SpectrumStyle::Mirrored => 2 * B as i32,
+            _ => B as i32,
+        };
+        for slot in 0..slots {
+            let band = match self.style {
+                SpectrumStyle::Mirrored if slot < B as i32 => B - 1 - slot as usize,
+                SpectrumStyle::Mirrored => slot as usize - B,
+                _ => slot as usize,
+            };
+            let x = rect.x + slot * rect.width / slots;
+            let end = rect.x + (slot + 1) * rect.width / slots;
+            let width = if end - x > 1 { end - x - 1 } else { end - x };
+            self.draw_column(canvas, Rect::new(x, rect.y, width, rect.height), band);
+        }
+    }
+
+    /// Draws each band as a horizontal bar filling from the left, one above the other
+    pub fn draw_vu<C: Canvas + ?Sized>(&self, canvas: &mut C, rect: Rect) {
+        fill_rect(canvas, rect, Pen::Clear);
+        for band in 0..B {
+            let y = rect.y + band as i32 * rect.height / B as i32;
+            let end = rect.y + (band as i32 + 1) * rect.height / B as i32;
+            let length = self.levels[band] * rect.width / ONE;
+            fill_rect(canvas, Rect::new(rect.x, y, length, end - y), Pen::Set);
+            if self.show_peaks && self.peaks[band] > 0 {
+                let peak_x = rect.x + (self.peaks[band] * rect.width / ONE - 1).max(0);
+                fill_rect(canvas, Rect::new(peak_x, y, 1, end - y), Pen::Set);
+            }
+        }
+    }
+
+    fn draw_column<C: Canvas + ?Sized>(&self, canvas: &mut C, column: Rect, band: usize) {
+        let height = self.levels[band] * column.height / ONE;
+        let peak = self.peaks[band] * column.height / ONE;
+        // rounding can reach a row past an odd height column so keep everything inside it
+        let mut fill = |rows: Rect| fill_rect(canvas, rows.intersect(&column), Pen::Set);
+        match self.style {
+            SpectrumStyle::Centered => {
+                // grows by a pixel on each side of the middle for every two pixels of height
+                let middle = column.y + column.height / 2;
+                let half = (height + 1) / 2;
+                fill(Rect::new(column.x, middle - half, column.width, 2 * half));
+                if self.show_peaks && peak > height {
+                    let peak_half = (peak + 1) / 2;
+                    fill(Rect::new(column.x, middle - peak_half, column.width, 1));
+                    fill(Rect::new(column.x, middle + peak_half - 1, column.width, 1));
+                }
+            }
+            _ => {
+                let bottom = column.bottom();
+                fill(Rect::new(column.x, bottom - height, column.width, height));
+                if self.show_peaks && peak > height {
+                    fill(Rect::new(column.x, bottom - peak, column.width, 1));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FrameBuffer;
+
+    #[test]
+    fn stays_inside_odd_height_rects() {
+        let styles = [
+            SpectrumStyle::Bottom,
+            SpectrumStyle::Centered,
+            SpectrumStyle::Mirrored,
+        ];
+        for style in styles {
+            for height in 1..=7 {
+                let mut spectrum = Spectrum::<4>::new(Range::new(0, 100), style);
+                spectrum.update(&[100, 70, 45, 10]);
+                spectrum.update(&[0, 0, 0, 0]);
+                let mut fb = FrameBuffer::<4>::new();
+                let rect = Rect::new(0, 1, 32, height);
+                spectrum.draw(&mut fb, rect);
+                for y in 0..8 {
+                    for x in 0..32 {
+                        if fb.pixel(x, y) {
+                            assert!(rect.contains(x, y), "{:?} height {}", style, height);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn columns_fall_slowly_and_peaks_hold() {
+        let mut spectrum = Spectrum::<1>::new(Range::new(0, 100), SpectrumStyle::Bottom);
+        spectrum.update(&[100]);
+        spectrum.update(&[0]);
+        assert_eq!(spectrum.levels[0], ONE - spectrum.decay);
+        assert_eq!(spectrum.peaks[0], ONE);
+        for _ in 0..spectrum.peak_hold_updates {
+            spectrum.update(&[0]);
+        }
+        assert_eq!(spectrum.peaks[0], ONE - spectrum.peak_decay);
+    }
+}
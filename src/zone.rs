@@ -0,0 +1,184 @@
+use crate::canvas::{Canvas, Window};
+use crate::font::Font;
+use crate::framebuffer::FrameBuffer;
+use crate::marquee::Marquee;
+use crate::text::{draw_markup, Direction};
+use crate::{Error, MAX7219};
+use embedded_hal::{blocking::spi::Transfer, digital::v2::OutputPin};
+
+/// Something that can be shown in a zone
+pub trait ZoneContent {
+    /// Draws onto the zone (the canvas is only as big as the zone) and returns true if
+    /// anything changed. now_ms is a millisecond timestamp
+    fn update(&mut self, canvas: &mut dyn Canvas, now_ms: u32) -> bool;
+}
+
+/// Any closure taking the zone canvas and a timestamp can be used as content
+impl<T: FnMut(&mut dyn Canvas, u32) -> bool> ZoneContent for T {
+    fn update(&mut self, canvas: &mut dyn Canvas, now_ms: u32) -> bool {
+        self(canvas, now_ms)
+    }
+}
+
+/// A message scrolled across the zone with a Marquee in its own font
+pub struct ScrollingText<'a, F> {
+    pub marquee: Marquee<'a>,
+    pub font: F,
+}
+
+impl<'a, F: Font> ZoneContent for ScrollingText<'a, F> {
+    fn update(&mut self, canvas: &mut dyn Canvas, now_ms: u32) -> bool {
+        if !self.marquee.tick(now_ms) {
+            return false;
+        }
+        self.marquee.draw(canvas, &self.font);
+        true
+    }
+}
+
+/// A message that stays still, only redrawn when blinking text changes or the
+/// message is replaced
+pub struct StaticText<'a, F> {
+    markup: &'a str,
+    font: F,
+    direction: Direction,
+    blink_ms: u32,
+    blink_on: Option<bool>,
+}
+
+impl<'a, F: Font> StaticText<'a, F> {
+    pub fn new(markup: &'a str, font: F) -> Self {
+        StaticText {
+            markup,
+            font,
+            direction: Direction::LeftToRight,
+            blink_ms: 500,
+            blink_on: None,
+        }
+    }
+
+    /// Replaces the message, it will be drawn on the next update
+    pub fn set_markup(&mut self, markup: &'a str) {
+        self.markup = markup;
+        self.blink_on = None;
+    }
+
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+        self.blink_on = None;
+    }
+}
+
+impl<'a, F: Font> ZoneContent for StaticText<'a, F> {
+    fn update(&mut self, canvas: &mut dyn Canvas, now_ms: u32) -> bool {
+        let blink_on = (now_ms / self.blink_ms) & 1 == 0;
+        if self.blink_on == Some(blink_on) {
+            return false;
+        }
+        self.blink_on = Some(blink_on);
+
+        let x = match self.direction {
+            Direction::RightToLeft => canvas.width(),
+            _ => 0,
+        };
+        canvas.clear();
+        draw_markup(
+            canvas,
+            &self.font,
+            self.markup,
+            x,
+            0,
+            self.direction,
+            blink_on,
+        );
+        true
+    }
+}
+
+/// A run of neighbouring devices in the chain with its own content and refresh interval.
+/// All the devices of a zone must be in the same row of tiles
+pub struct Zone<'a> {
+    first_device: usize,
+    num_devices: usize,
+    interval_ms: u32,
+    last_update_ms: Option<u32>,
+    content: &'a mut dyn ZoneContent,
+}
+
+impl<'a> Zone<'a> {
+    /// A zone covering num_devices devices starting at first_device (device 0 is the closest to
+    /// the microcontroller). The content is updated every time the zones are updated
+    pub fn new(first_device: usize, num_devices: usize, content: &'a mut dyn ZoneContent) -> Self {
+        Zone {
+            first_device,
+            num_devices,
+            interval_ms: 0,
+            last_update_ms: None,
+            content,
+        }
+    }
+
+    /// Only update the content when at least interval_ms has passed since the last update
+    pub fn set_interval(&mut self, interval_ms: u32) {
+        self.interval_ms = interval_ms;
+    }
+
+    fn is_due(&self, now_ms: u32) -> bool {
+        match self.last_update_ms {
+            Some(last) => now_ms.wrapping_sub(last) >= self.interval_ms,
+            None => true,
+        }
+    }
+}
+
+/// Splits one daisy chain into independent zones which share a single framebuffer so that a
+/// flush sends every zone in the same eight write_line_raw transactions
+pub struct Zones<'a, 'z, const N: usize> {
+    zones: &'z mut [Zone<'a>],
+    framebuffer: FrameBuffer<N>,
+}
+
+impl<'a, 'z, const N: usize> Zones<'a, 'z, N> {
+    pub fn new(zones: &'z mut [Zone<'a>], framebuffer: FrameBuffer<N>) -> Self {
+        Zones { zones, framebuffer }
+    }
+
+    /// Updates every zone that is due and returns true if any of them changed
+    pub fn update(&mut self, now_ms: u32) -> bool {
+        let columns = self.framebuffer.columns();
+        let mut changed = false;
+        for zone in self.zones.iter_mut() {
+            if !zone.is_due(now_ms) {
+                continue;
+            }
+            zone.last_update_ms = Some(now_ms);
+
+            let x = (zone.first_device % columns) as i32 * 8;
+            let y = (zone.first_device / columns) as i32 * 8;
+            let width = zone.num_devices as i32 * 8;
+            let mut window = Window::new(&mut self.framebuffer, x, y, width, 8);
+            changed |= zone.content.update(&mut window, now_ms);
+        }
+        changed
+    }
+
+    /// Sends the combined zones to the chips
+    pub fn flush<CS, PinError, SpiError>(
+        &self,
+        max7219: &mut MAX7219<CS>,
+        spi: &mut dyn Transfer<u8, Error = SpiError>,
+    ) -> Result<(), Error<SpiError, PinError>>
+    where
+        CS: OutputPin<Error = PinError>,
+    {
+        max7219.write_framebuffer(spi, &self.framebuffer)
+    }
+
+    pub fn framebuffer(&self) -> &FrameBuffer<N> {
+        &self.framebuffer
+    }
+
+    pub fn framebuffer_mut(&mut self) -> &mut FrameBuffer<N> {
+        &mut self.framebuffer
+    }
+}